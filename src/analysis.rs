@@ -23,20 +23,34 @@
  *                                                                           *
  * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use config::Config;
+use config::{Config, InputFormat};
 use log::*;
+use reader::{self, Lines};
+use registry::Registry;
+use report::Report;
+use runsc;
 use summary;
-use summary::GenericFileDescriptor;
 use summary::Summary;
+use summary::{AccessMode, GenericFileDescriptor};
 
 use regex::Regex;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
 
-fn dup(fds: &mut HashMap<u32, Summary>, syscall: &str, oldfd: &u32, newfd: u32, config: &Config) {
-    let summary = if let Some(summary_old) = fds.get(&oldfd) {
+// handled inline by the analysis itself, all others are dispatched to the
+// registry, everything else is reported by --unknown-syscalls
+const SYSCALLS: &[&str] = &["clone", "clone3"];
+
+fn dup(
+    fds: &mut HashMap<u32, Summary>,
+    syscall: &str,
+    oldfd: &u32,
+    newfd: u32,
+    report: &mut Report,
+    config: &Config,
+) {
+    let summary = if let Some(summary_old) = fds.get(oldfd) {
         let old_file = &summary_old.descriptor;
 
         debug(
@@ -44,39 +58,56 @@ fn dup(fds: &mut HashMap<u32, Summary>, syscall: &str, oldfd: &u32, newfd: u32,
             config,
         );
 
-        Summary::new(old_file.clone())
+        // the status flags belong to the open file description, which is
+        // shared, unlike the fd flags like FD_CLOEXEC
+        let mut summary = Summary::new(old_file.clone());
+        summary.set_nonblocking(summary_old.nonblocking);
+        summary
     } else {
         debug(
             format!("[{}] couldn't find oldfd {}", syscall, oldfd),
             config,
         );
 
-        Summary::file(String::from("DUP"))
+        Summary::new(GenericFileDescriptor::Duplicate(*oldfd))
     };
 
-    insert(fds, newfd, summary, syscall, config);
+    insert(fds, newfd, summary, syscall, report, config);
 }
 
-fn finish(fds: &mut HashMap<u32, Summary>, fd: u32, syscall: &str, config: &Config) {
-    if let Some(summary) = fds.remove(&fd) {
+fn finish(
+    fds: &mut HashMap<u32, Summary>,
+    fd: u32,
+    syscall: &str,
+    report: &mut Report,
+    config: &Config,
+) {
+    if let Some(mut summary) = fds.remove(&fd) {
         debug(
             format!("[{}] {} => {}", syscall, fd, summary.descriptor),
             config,
         );
 
-        summary.show(config);
+        summary.set_closed(report.time);
+
+        report.push(summary, config);
     } else {
         verbose(format!("[{}] unknown fd {}", syscall, fd), config);
+        report.unknown_close();
     }
 }
 
 fn insert(
     fds: &mut HashMap<u32, Summary>,
     fd: u32,
-    summary: Summary,
+    mut summary: Summary,
     syscall: &str,
+    report: &mut Report,
     config: &Config,
 ) {
+    summary.set_opened(report.time);
+    summary.set_opened_op(report.seq);
+
     if let Some(summary) = fds.insert(fd, summary) {
         debug(
             format!(
@@ -86,11 +117,42 @@ fn insert(
             config,
         );
 
-        summary.show(config)
+        report.push(summary, config)
     };
+
+    report.update_open(fds.len());
+
+    if let Some(max) = config.max_descriptors {
+        evict(fds, fd, max, report, config);
+    }
+}
+
+// keeps the fd table bounded by handing the least recently active fds, other
+// than the one just opened, to the report before they are closed
+fn evict(
+    fds: &mut HashMap<u32, Summary>,
+    opened: u32,
+    max: usize,
+    report: &mut Report,
+    config: &Config,
+) {
+    while fds.len() > max {
+        let oldest = fds
+            .iter()
+            .filter(|(fd, _)| **fd != opened)
+            .min_by_key(|(fd, summary)| (summary.last_active(), **fd))
+            .map(|(fd, _)| *fd);
+
+        match oldest.and_then(|fd| fds.remove(&fd)) {
+            Some(summary) => report.evict(summary, config),
+            None => break,
+        }
+    }
 }
 
 fn join_paths(fds: &HashMap<u32, Summary>, dirfd: &str, pathname: &str) -> String {
+    let pathname = &unescape(pathname);
+
     match dirfd {
         "AT_FDCWD" => String::from(pathname),
         fd_str => {
@@ -103,7 +165,7 @@ fn join_paths(fds: &HashMap<u32, Summary>, dirfd: &str, pathname: &str) -> Strin
                 {
                     let mut path = PathBuf::new();
                     path.push(file_description.path.clone());
-                    path.push(pathname.clone());
+                    path.push(pathname);
 
                     if let Some(path) = path.to_str() {
                         effective_path = String::from(path);
@@ -115,344 +177,2185 @@ fn join_paths(fds: &HashMap<u32, Summary>, dirfd: &str, pathname: &str) -> Strin
     }
 }
 
-pub fn analyze(fds: &mut HashMap<u32, Summary>, input: &Path, config: &Config) -> io::Result<()> {
-    let file = File::open(input)?;
+/// Decodes the escapes strace uses in strings: `\xNN` with `-x` and `-xx`,
+/// `\NNN` octal escapes for non-printable bytes otherwise, and the C escapes
+/// for quotes, backslashes and whitespace. Bytes that aren't UTF-8 are
+/// replaced.
+///
+/// ```
+/// use strace_analyzer::analysis::unescape;
+///
+/// assert_eq!(unescape(r"\x2f\x73\x72\x76/caf\xc3\xa9"), "/srv/café");
+/// assert_eq!(unescape(r"/srv/caf\303\251"), "/srv/café");
+/// assert_eq!(unescape("/srv/\\\"quoted\\\""), "/srv/\"quoted\"");
+/// ```
+pub fn unescape(s: &str) -> String {
+    if !s.contains('\\') {
+        return String::from(s);
+    }
+
+    let chars: Vec<char> = s.chars().collect();
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut i = 0;
+
+    // the digits of an escape, at most `len` of them in `radix`
+    let digits = |from: usize, len: usize, radix: u32| {
+        chars[from..]
+            .iter()
+            .take(len)
+            .take_while(|c| c.is_digit(radix))
+            .count()
+    };
 
-    lazy_static! {
-        static ref RE_CLONE: Regex = Regex::new(r#"^clone\(.*\)\s+= (\d+)$"#).unwrap();
+    while i < chars.len() {
+        let c = chars[i];
+        if c != '\\' || i + 1 == chars.len() {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            i += 1;
+            continue;
+        }
+
+        let (byte, len) = match chars[i + 1] {
+            'x' if digits(i + 2, 2, 16) == 2 => {
+                let hex: String = chars[i + 2..i + 4].iter().collect();
+                (u8::from_str_radix(&hex, 16).ok(), 4)
+            }
+            '0'..='7' => {
+                let n = digits(i + 1, 3, 8);
+                let octal: String = chars[i + 1..i + 1 + n].iter().collect();
+                (u8::from_str_radix(&octal, 8).ok(), 1 + n)
+            }
+            'n' => (Some(b'\n'), 2),
+            't' => (Some(b'\t'), 2),
+            'r' => (Some(b'\r'), 2),
+            'v' => (Some(0x0b), 2),
+            'f' => (Some(0x0c), 2),
+            '"' => (Some(b'"'), 2),
+            '\\' => (Some(b'\\'), 2),
+            _ => (None, 1),
+        };
+
+        match byte {
+            Some(byte) => bytes.push(byte),
+            None => bytes.push(b'\\'),
+        }
+        i += len;
     }
 
-    lazy_static! {
-        static ref RE_CLOSE: Regex =
-            Regex::new(r#"^close\((\d+)\)\s+= (-?\d+)\s*([A-Z]*).*$"#).unwrap();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// relabels the open and the already closed descriptors of a renamed file
+fn rename(
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    from: &str,
+    to: &str,
+    exchange: bool,
+    config: &Config,
+) {
+    debug(format!("[rename] {} => {}", from, to), config);
+
+    for summary in fds.values_mut() {
+        summary.rename(from, to, exchange);
     }
 
-    lazy_static! {
-        static ref RE_CREAT: Regex = Regex::new(r#"^creat\("([^"]+)", .+\)\s+= (\d+)$"#).unwrap();
+    report.rename(from, to, exchange);
+}
+
+// O_TMPFILE opens an unnamed file in the directory given as the path. the
+// flags come first in `args`, before the mode.
+fn tmpfile(path: String, args: &str) -> String {
+    let flags = args.split(", ").next().unwrap_or("");
+
+    if flags.split('|').any(|flag| flag == "O_TMPFILE") {
+        format!("(O_TMPFILE in {})", path)
+    } else {
+        path
     }
+}
 
-    lazy_static! {
-        static ref RE_DUP: Regex = Regex::new(r#"^dup\((\d+)\)\s+= (\d+)$"#).unwrap();
+// well-known symlinks to already open file descriptors
+fn linked_fd(path: &str) -> Option<u32> {
+    match path {
+        "/dev/stdin" => Some(0),
+        "/dev/stdout" => Some(1),
+        "/dev/stderr" => Some(2),
+        _ => ["/proc/self/fd/", "/dev/fd/"]
+            .iter()
+            .find(|prefix| path.starts_with(*prefix))
+            .and_then(|prefix| path[prefix.len()..].parse().ok()),
     }
+}
 
-    lazy_static! {
-        static ref RE_DUP2: Regex = Regex::new(r#"^dup2\((\d+), \d+\)\s+= (\d+)$"#).unwrap();
+fn open(fds: &HashMap<u32, Summary>, path: String, syscall: &str, config: &Config) -> Summary {
+    if let Some(target) = linked_fd(&path).and_then(|fd| fds.get(&fd)) {
+        debug(
+            format!("[{}] {} => {}", syscall, path, target.descriptor),
+            config,
+        );
+
+        Summary::new(target.descriptor.clone())
+    } else if summary::is_tty(&path) {
+        Summary::tty(path)
+    } else {
+        Summary::file(path)
     }
+}
 
-    lazy_static! {
-        static ref RE_FCNTL_DUP: Regex =
-            Regex::new(r#"^fcntl\((\d+), F_DUPFD, \d+\)\s+= (\d+)$"#).unwrap();
+struct Op {
+    fd: u32,
+    op_size: u64,
+    bytes: u64,
+    // positioned I/O brings its own offset, everything else uses and
+    // advances the file pointer
+    offset: Option<u64>,
+}
+
+impl Op {
+    fn new(fd: u32, op_size: u64, bytes: u64) -> Op {
+        Op {
+            fd,
+            op_size,
+            bytes,
+            offset: None,
+        }
     }
 
-    lazy_static! {
-        static ref RE_OPEN: Regex = Regex::new(
-            // we're ignoring failures on purpose because they don't open fd
-            r#"^open\("([^"]+)", .+\)\s+= (\d+)$"#
-        ).unwrap();
+    fn at(fd: u32, op_size: u64, bytes: u64, offset: u64) -> Op {
+        Op {
+            offset: Some(offset),
+            ..Op::new(fd, op_size, bytes)
+        }
     }
+}
 
-    lazy_static! {
-        static ref RE_OPENAT: Regex =
-            Regex::new(r#"^openat\((\d+|AT_FDCWD), "([^"]+)", .+\)\s+= (\d+)$"#).unwrap();
+fn read(
+    fds: &mut HashMap<u32, Summary>,
+    op: Op,
+    syscall: &str,
+    report: &mut Report,
+    config: &Config,
+) {
+    if let Some(summary) = fds.get_mut(&op.fd) {
+        // reads that hit the end of the file are kept even with
+        // --exclude-zero-byte-ops, they are counted as EOF
+        summary.update_read(op.op_size, op.bytes, report.seq);
+        summary.update_first_io(report.time);
+        summary.update_position(op.offset, op.bytes);
+        report.update_read(summary, op.bytes, config);
+        report.transferred(syscall, op.bytes);
+    } else {
+        verbose(format!("[{}] unknown fd {}", syscall, op.fd), config);
     }
+}
 
-    lazy_static! {
-        static ref RE_PIPE: Regex = Regex::new(r#"^pipe\(\[(\d+), (\d+)\]\)\s+= (\d+)$"#).unwrap();
+fn write(
+    fds: &mut HashMap<u32, Summary>,
+    op: Op,
+    syscall: &str,
+    report: &mut Report,
+    config: &Config,
+) {
+    if let Some(summary) = fds.get_mut(&op.fd) {
+        // zero-byte writes transfer nothing and only inflate the op counts
+        if op.bytes == 0 && config.exclude_zero_byte_ops {
+            debug(
+                format!("[{}] {} => zero-byte write", syscall, op.fd),
+                config,
+            );
+            return;
+        }
+
+        summary.update_write(op.op_size, op.bytes, report.seq);
+        summary.update_first_io(report.time);
+        summary.update_extent(op.offset, op.bytes);
+        summary.update_position(op.offset, op.bytes);
+        report.update_write(summary, op.bytes, config);
+        report.transferred(syscall, op.bytes);
+    } else {
+        verbose(format!("[{}] unknown fd {}", syscall, op.fd), config);
     }
+}
 
+fn iov_len(iov: &str) -> u64 {
     lazy_static! {
-        static ref RE_PREAD: Regex =
-            Regex::new(r#"^pread\((\d+),.*, (\d+), \d+\)\s+= (\d+)$"#).unwrap();
+        static ref RE_IOV_LEN: Regex = Regex::new(r#"iov_len=(\d+)"#).unwrap();
     }
 
+    RE_IOV_LEN
+        .captures_iter(iov)
+        .map(|cap| cap[1].parse::<u64>().unwrap())
+        .sum()
+}
+
+// (requested, transferred) bytes of the messages of recvmmsg/sendmmsg, every
+// mmsghdr carries its own iovecs and the msg_len the kernel filled in
+fn mmsg_lens(msgvec: &str, count: usize) -> Vec<(u64, u64)> {
     lazy_static! {
-        static ref RE_PWRITE: Regex =
-            Regex::new(r#"^pwrite\((\d+),.*, (\d+), \d+\)\s+= (\d+)$"#).unwrap();
+        static ref RE_MSG_LEN: Regex = Regex::new(r#"msg_len=(\d+)"#).unwrap();
     }
 
+    msgvec
+        .split("{msg_hdr=")
+        .skip(1)
+        .filter_map(|msg| {
+            let cap = RE_MSG_LEN.captures(msg)?;
+            Some((iov_len(msg), cap[1].parse().unwrap()))
+        })
+        .take(count)
+        .collect()
+}
+
+// 32-bit and legacy names of syscalls taking the same arguments
+const ALIASES: &[(&str, &str)] = &[
+    ("fcntl64", "fcntl"),
+    ("pread64", "pread"),
+    ("preadv2", "preadv"),
+    ("pwrite64", "pwrite"),
+    ("pwritev2", "pwritev"),
+];
+
+// the subfunctions of socketcall by their number, starting at 1
+const SOCKETCALLS: &[&str] = &[
+    "socket",
+    "bind",
+    "connect",
+    "listen",
+    "accept",
+    "getsockname",
+    "getpeername",
+    "socketpair",
+    "send",
+    "recv",
+    "sendto",
+    "recvfrom",
+    "shutdown",
+    "setsockopt",
+    "getsockopt",
+    "sendmsg",
+    "recvmsg",
+    "accept4",
+    "recvmmsg",
+    "sendmmsg",
+];
+
+/// Rewrites the 32-bit and legacy variants of syscalls to the names the
+/// handlers know, `None` if the line needs no rewriting. `_llseek` returns
+/// the resulting offset in its third argument instead of its return value.
+/// The socket syscalls multiplexed by `socketcall` on e.g. i386 are rewritten
+/// to the syscalls they stand for.
+///
+/// ```
+/// use strace_analyzer::analysis::legacy;
+///
+/// assert_eq!(
+///     legacy("_llseek(3, 8192, [8192], SEEK_SET) = 0").unwrap(),
+///     "lseek(3, 8192, SEEK_SET) = 8192",
+/// );
+/// assert_eq!(legacy("lseek(3, 0, SEEK_SET) = 0"), None);
+/// assert_eq!(
+///     legacy("socketcall(SYS_SEND, [3, \"\", 100, 0]) = 100").unwrap(),
+///     "sendto(3, \"\", 100, 0, NULL, 0) = 100",
+/// );
+/// ```
+pub fn legacy(line: &str) -> Option<String> {
     lazy_static! {
-        static ref RE_READ: Regex = Regex::new(r#"^read\((\d+),.*, (\d+)\)\s+= (\d+)$"#).unwrap();
+        static ref RE_LLSEEK: Regex =
+            Regex::new(r#"^_llseek\((\d+), (-?\d+), \[(\d+)\], (SEEK_\w+)\)\s+= 0$"#).unwrap();
     }
 
     lazy_static! {
-        static ref RE_SOCKET: Regex = Regex::new(r#"^socket\(.*\)\s+= (\d+)$"#).unwrap();
+        static ref RE_SOCKETCALL: Regex =
+            Regex::new(r#"^socketcall\((?:SYS_(\w+)|(0x[0-9a-f]+|\d+)), \[(.*)\]\)\s+= (.+)$"#)
+                .unwrap();
     }
 
-    lazy_static! {
-        static ref RE_ACCEPT: Regex =
-            Regex::new(r#"^accept\((\d+), (.*), .*\)\s+= (\d+)$"#).unwrap();
+    if let Some(cap) = RE_LLSEEK.captures(line) {
+        return Some(format!(
+            "lseek({}, {}, {}) = {}",
+            &cap[1], &cap[2], &cap[4], &cap[3]
+        ));
     }
 
-    lazy_static! {
-        static ref RE_BIND: Regex =
-            Regex::new(r#"^bind\((\d+), (.*), \d+\)\s+= (\d+).*$"#).unwrap();
+    if let Some(cap) = RE_SOCKETCALL.captures(line) {
+        let syscall = match (cap.get(1), cap.get(2)) {
+            (Some(name), _) => name.as_str().to_lowercase(),
+            (_, Some(number)) => {
+                let number = match number.as_str().strip_prefix("0x") {
+                    Some(hex) => usize::from_str_radix(hex, 16).ok()?,
+                    None => number.as_str().parse().ok()?,
+                };
+                String::from(*SOCKETCALLS.get(number.checked_sub(1)?)?)
+            }
+            _ => return None,
+        };
+
+        // send and recv are sendto and recvfrom without an address
+        return Some(match syscall.as_str() {
+            "send" => format!("sendto({}, NULL, 0) = {}", &cap[3], &cap[4]),
+            "recv" => format!("recvfrom({}, NULL, 0) = {}", &cap[3], &cap[4]),
+            _ => format!("{}({}) = {}", syscall, &cap[3], &cap[4]),
+        });
     }
 
+    ALIASES.iter().find_map(|(alias, syscall)| {
+        let args = line.strip_prefix(alias)?;
+
+        if args.starts_with('(') {
+            Some(format!("{}{}", syscall, args))
+        } else {
+            None
+        }
+    })
+}
+
+pub fn strip_prefix(line: &str) -> (Option<u32>, Option<f64>, &str) {
     lazy_static! {
-        static ref RE_CONNECT: Regex =
-            Regex::new(r#"^connect\((\d+), (.*), \d+\)\s+= (\d+).*$"#).unwrap();
+        static ref RE_PREFIX: Regex = Regex::new(r#"^(?:\[pid\s+(\d+)\]|(\d+))\s+"#).unwrap();
     }
 
     lazy_static! {
-        static ref RE_SENDTO: Regex =
-            Regex::new(r#"^sendto\((\d+),.*, (\d+), .*, .*, .*\)\s+= (\d+)$"#).unwrap();
+        // -t and -tt print the time of day, -ttt prints seconds since epoch
+        static ref RE_TIMESTAMP: Regex =
+            Regex::new(r#"^(?:(\d\d):(\d\d):(\d\d(?:\.\d+)?)|(\d+\.\d+))\s+"#).unwrap();
     }
 
-    lazy_static! {
-        static ref RE_RECVFROM: Regex =
-            Regex::new(r#"^recvfrom\((\d+),.*, (\d+), .*, .*, .*\)\s+= (\d+)$"#).unwrap();
+    let (pid, line) = if let Some(cap) = RE_PREFIX.captures(line) {
+        let pid = cap.get(1).or_else(|| cap.get(2)).unwrap();
+        let rest = &line[cap.get(0).unwrap().end()..];
+        (pid.as_str().parse().ok(), rest)
+    } else {
+        (None, line)
+    };
+
+    if let Some(cap) = RE_TIMESTAMP.captures(line) {
+        let time = if let Some(epoch) = cap.get(4) {
+            epoch.as_str().parse().unwrap()
+        } else {
+            let hours: f64 = cap[1].parse().unwrap();
+            let minutes: f64 = cap[2].parse().unwrap();
+            let seconds: f64 = cap[3].parse().unwrap();
+            hours * 3600.0 + minutes * 60.0 + seconds
+        };
+        let rest = &line[cap.get(0).unwrap().end()..];
+        (pid, Some(time), rest)
+    } else {
+        (pid, None, line)
     }
+}
+
+pub fn pid(input: &Path) -> String {
+    match input.extension().and_then(|pid| pid.to_str()) {
+        Some(pid) if pid.parse::<u32>().is_ok() => String::from(pid),
+        _ => input.display().to_string(),
+    }
+}
+
+/// The descriptors every process starts with. Redirecting them with `dup2`
+/// or `dup3` attributes the I/O to the file they were redirected to.
+pub fn standard_fds() -> HashMap<u32, Summary> {
+    let mut fds = HashMap::new();
+
+    fds.insert(0, Summary::file(String::from("STDIN")));
+    fds.insert(1, Summary::file(String::from("STDOUT")));
+    fds.insert(2, Summary::file(String::from("STDERR")));
+
+    fds
+}
 
+/// Analyzes a strace log, passing each summary to `callback` as soon as its
+/// descriptor is closed instead of collecting them in the report.
+pub fn analyze_with<'a, F>(input: &Path, config: &Config, callback: F) -> io::Result<Report<'a>>
+where
+    F: FnMut(Summary) + 'a,
+{
+    let mut report = Report::with_sink(callback);
+    analyze(&mut standard_fds(), input, &mut report, config)?;
+    Ok(report)
+}
+
+/// Analyzes a strace log into `report`, starting with the descriptors in
+/// `fds`. Files opened with `O_TMPFILE` are labeled by their directory until
+/// `linkat` gives them a name.
+///
+/// With `--follow`, the log may be a FIFO fed by a live strace: pauses and a
+/// reconnecting writer don't end the analysis, the exit of the traced program
+/// does.
+///
+/// The traces `strace -ff` writes for the children created with `clone` or
+/// `clone3` are analyzed as well. Children get a copy of the fd table unless
+/// created with `CLONE_FILES`, and threads created with `CLONE_THREAD` count
+/// for their process.
+///
+/// With `--exclude-zero-byte-ops`, writes of zero bytes don't count as ops.
+pub fn analyze(
+    fds: &mut HashMap<u32, Summary>,
+    input: &Path,
+    report: &mut Report,
+    config: &Config,
+) -> io::Result<()> {
     lazy_static! {
-        static ref RE_WRITE: Regex = Regex::new(r#"^write\((\d+),.*, (\d+)\)\s+= (\d+)$"#).unwrap();
+        static ref BUILTIN: Registry = Registry::default();
     }
 
-    for l in BufReader::new(file).lines() {
-        let line = l?;
+    analyze_with_registry(fds, input, &BUILTIN, report, config)
+}
 
-        for cap in RE_CREAT.captures_iter(&line) {
-            let file = String::from(&cap[1]);
-            let fd: u32 = cap[2].parse().unwrap();
+/// Analyzes a strace log like `analyze`, dispatching the syscalls to the
+/// handlers in `registry` instead of the built-in ones.
+pub fn analyze_with_registry(
+    fds: &mut HashMap<u32, Summary>,
+    input: &Path,
+    registry: &Registry,
+    report: &mut Report,
+    config: &Config,
+) -> io::Result<()> {
+    let process = trace(fds, input, pid(input), registry, report, config)?;
 
-            debug(format!("[creat] {} => {}", fd, file), config);
+    // what's still open at the end is the traced program's
+    let parent = report.enter(process);
 
-            let syscall = "creat";
-            insert(fds, fd, Summary::file(file), syscall, config);
-        }
+    for (_, summary) in fds.drain() {
+        report.push(summary, config);
+    }
 
-        for cap in RE_CLOSE.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
-            let status: i32 = cap[2].parse().unwrap();
-            let error = &cap[3];
-            let syscall = "close";
+    if let Some(summary) = report.take_entropy() {
+        report.push(summary, config);
+    }
 
-            match (status, error) {
-                (0, _) => finish(fds, fd, syscall, config),
+    report.leave(parent);
 
-                (_, "EBADF") => debug(format!("[close] {} => bad fd", fd), config),
+    Ok(())
+}
 
-                (_, error) => {
-                    verbose(format!("[close] {} => {}", fd, error), config);
-                    finish(fds, fd, syscall, config)
-                }
+// under -f, the lines of all processes end up in one trace, told apart by
+// their pid prefix. each process has its own fd table, unless it shares the
+// one of its parent by CLONE_FILES, as threads do. lines without a prefix are
+// the traced program's, which uses the table of the trace.
+#[derive(Default)]
+struct Processes {
+    // the traced program, once one of its lines has a prefix
+    root: Option<u32>,
+    fds: BTreeMap<u32, HashMap<u32, Summary>>,
+    // the pid whose fd table a pid uses and the leader of its thread group,
+    // None for the traced program's
+    owners: HashMap<u32, Option<u32>>,
+    leaders: HashMap<u32, Option<u32>>,
+}
+
+impl Processes {
+    // the first pid without a parent is the traced program, any later ones
+    // were attached to and start out with just the standard streams
+    fn owner(&mut self, pid: Option<u32>) -> Option<u32> {
+        let pid = pid?;
+
+        if !self.owners.contains_key(&pid) {
+            if self.root.is_none() {
+                self.root = Some(pid);
+                self.owners.insert(pid, None);
+                self.leaders.insert(pid, None);
+            } else {
+                self.fds.insert(pid, standard_fds());
+                self.owners.insert(pid, Some(pid));
+                self.leaders.insert(pid, Some(pid));
             }
         }
 
-        for cap in RE_CLONE.captures_iter(&line) {
-            let pid = &cap[1];
+        self.owners[&pid]
+    }
 
-            let trace = Path::new(&input).with_extension(pid);
+    fn leader(&self, pid: Option<u32>) -> Option<u32> {
+        pid.and_then(|pid| self.leaders[&pid])
+    }
 
-            verbose(
-                format!("[clone] tracing pid {} in {:?} ...", pid, trace),
-                config,
-            );
+    // the process the I/O of `leader` is attributed to in the report
+    fn name(&self, leader: Option<u32>, process: &str) -> String {
+        leader.map_or_else(|| String::from(process), |pid| pid.to_string())
+    }
 
-            let mut cfds = fds.clone();
+    fn table<'t>(
+        &'t mut self,
+        owner: Option<u32>,
+        root: &'t mut HashMap<u32, Summary>,
+    ) -> &'t mut HashMap<u32, Summary> {
+        match owner {
+            Some(owner) => self.fds.get_mut(&owner).unwrap(),
+            None => root,
+        }
+    }
 
-            for (_, summary) in cfds.iter_mut() {
+    // children get a copy of the fds of their parent, unless they share them
+    fn clone(
+        &mut self,
+        parent: Option<u32>,
+        child: u32,
+        flags: &str,
+        root: &mut HashMap<u32, Summary>,
+    ) {
+        let owner = self.owner(parent);
+        let leader = if flags.contains("CLONE_THREAD") {
+            self.leader(parent)
+        } else {
+            Some(child)
+        };
+
+        // the child's lines may come before the clone returned in the parent
+        let seen = match self.owners.get(&child) {
+            Some(&Some(owner)) if owner == child => self.fds.remove(&child),
+            _ => None,
+        };
+
+        let owner = if flags.contains("CLONE_FILES") {
+            owner
+        } else {
+            let mut fds = self.table(owner, root).clone();
+
+            for (_, summary) in fds.iter_mut() {
                 summary.reset();
             }
 
-            analyze(&mut cfds, &trace, config)?;
+            self.fds.insert(child, fds);
+            Some(child)
+        };
+
+        if let Some(seen) = seen {
+            let fds = self.table(owner, root);
 
-            verbose(format!("[clone] tracing pid {} finished", pid), config);
+            for (fd, summary) in seen {
+                match fds.get_mut(&fd) {
+                    Some(inherited) => inherited.merge(&summary),
+                    None => {
+                        fds.insert(fd, summary);
+                    }
+                }
+            }
         }
 
-        for cap in RE_DUP.captures_iter(&line) {
-            let oldfd: u32 = cap[1].parse().unwrap();
-            let newfd: u32 = cap[2].parse().unwrap();
+        self.owners.insert(child, owner);
+        self.leaders.insert(child, leader);
+    }
+}
 
-            dup(fds, "dup", &oldfd, newfd, config);
-        }
+// analyzes the trace of a process or thread, attributing its I/O to
+// `process` and leaving the fds that are still open in the table. returns the
+// process, known by its pid if the trace has prefixes.
+fn trace(
+    fds: &mut HashMap<u32, Summary>,
+    input: &Path,
+    mut process: String,
+    registry: &Registry,
+    report: &mut Report,
+    config: &Config,
+) -> io::Result<String> {
+    let file = reader::open(input, config)?;
+    let parent = report.enter(process.clone());
 
-        for cap in RE_DUP2.captures_iter(&line) {
-            let oldfd: u32 = cap[1].parse().unwrap();
-            let newfd: u32 = cap[2].parse().unwrap();
+    lazy_static! {
+        static ref RE_GRAMMAR: Regex = Regex::new(r#"^(?:\w+\(|<\.\.\. |\+\+\+ |--- )"#).unwrap();
+    }
 
-            dup(fds, "dup2", &oldfd, newfd, config);
-        }
+    lazy_static! {
+        static ref RE_SYSCALL: Regex = Regex::new(r#"^(\w+)\("#).unwrap();
+    }
 
-        for cap in RE_FCNTL_DUP.captures_iter(&line) {
-            let oldfd: u32 = cap[1].parse().unwrap();
-            let newfd: u32 = cap[2].parse().unwrap();
+    lazy_static! {
+        // a syscall either returned or is continued by a later resumed line
+        static ref RE_COMPLETE: Regex =
+            Regex::new(r#"(?:\)\s+= \S.*|<unfinished \.\.\.>)$"#).unwrap();
+    }
 
-            dup(fds, "fcntl-dup", &oldfd, newfd, config);
-        }
+    lazy_static! {
+        // failed transfers don't move any bytes, but retries are worth counting
+        static ref RE_IO_ERROR: Regex = Regex::new(
+            r#"^(?:read|readv|pread|preadv|write|writev|pwrite|pwritev|recvfrom|sendto|recvmmsg|sendmmsg)\((\d+),.*\)\s+= -1 (E[A-Z0-9]+)"#
+        ).unwrap();
+    }
 
-        for cap in RE_OPEN.captures_iter(&line) {
-            let file = String::from(&cap[1]);
-            let fd: u32 = cap[2].parse().unwrap();
+    lazy_static! {
+        // interrupted by a signal, the kernel restarts the syscall transparently
+        static ref RE_IO_RESTART: Regex = Regex::new(
+            r#"^(?:read|readv|pread|preadv|write|writev|pwrite|pwritev|recvfrom|sendto|recvmmsg|sendmmsg)\((\d+),.*\)\s+= \? ERESTART(?:SYS|NOINTR|NOHAND|_RESTARTBLOCK)\b"#
+        ).unwrap();
+    }
 
-            debug(format!("[open] {} => {}", fd, file), config);
+    lazy_static! {
+        static ref RE_EXIT: Regex = Regex::new(
+            r#"^\+\+\+ (exited with \d+|killed by SIG\w+(?: \(core dumped\))?) \+\+\+$"#
+        )
+        .unwrap();
+    }
 
-            let syscall = "open";
-            insert(fds, fd, Summary::file(file), syscall, config);
-        }
+    lazy_static! {
+        static ref RE_CLONE: Regex = Regex::new(r#"^(clone3?)\((.*)\)\s+= (\d+)$"#).unwrap();
+    }
 
-        for cap in RE_OPENAT.captures_iter(&line) {
-            let dirfd = &cap[1];
-            let pathname = &cap[2];
-            let fd: u32 = cap[3].parse().unwrap();
+    let mut lines = Lines::new(BufReader::new(file), config);
+    let mut processes = Processes::default();
 
-            let file = join_paths(fds, dirfd, pathname);
+    // when following, the exit of the traced program ends the log, not EOF
+    let mut first_pid = None;
+    let mut exited = false;
 
-            debug(format!("[openat] {} => {}", fd, file), config);
+    while !exited {
+        let line = match lines.next() {
+            Some(l) => l?,
+            None => break,
+        };
 
-            let syscall = "openat";
-            insert(fds, fd, Summary::file(file), syscall, config);
+        if let Some(limit) = config.limit_lines {
+            if lines.number() > limit {
+                report.truncate(limit);
+                break;
+            }
         }
 
-        for cap in RE_PIPE.captures_iter(&line) {
-            let readend = cap[1].parse().unwrap();
-            let writeend = cap[2].parse().unwrap();
+        // syscalls cut in two by <unfinished ...> are lost if only one half
+        // is sampled, just like at the start and the end of a trace
+        if let Some(k) = config.sample {
+            if !(lines.number() - 1).is_multiple_of(k) {
+                continue;
+            }
+        }
 
-            debug(format!("[pipe] {} => {}", readend, writeend), config);
+        let line = if config.input_format == InputFormat::Runsc {
+            match runsc::normalize(&line) {
+                Some(line) => line,
+                None => continue,
+            }
+        } else {
+            line
+        };
+
+        let (prefix_pid, time, line) = strip_prefix(&line);
+        let leader = *first_pid.get_or_insert(prefix_pid);
+        let unnamed = processes.root.is_none();
+        let owner = processes.owner(prefix_pid);
+        let group = processes.leader(prefix_pid);
+
+        // the traced program is known by its pid as soon as it has a prefix
+        if let Some(root) = processes.root.filter(|_| unnamed) {
+            report.rename_process(&process, root.to_string());
+            process = root.to_string();
+        }
 
-            let syscall = "pipe";
-            insert(fds, readend, Summary::pipe(), syscall, config);
-            insert(fds, writeend, Summary::pipe(), syscall, config);
+        let name = processes.name(group, &process);
+        if report.pid != name {
+            report.enter(name);
         }
 
-        for cap in RE_PREAD.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+        if config.lenient && !RE_GRAMMAR.is_match(line) {
+            debug(format!("[lenient] skipping {}", line), config);
+            report.skip();
+            continue;
+        }
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_read(opsize, bytes);
-            } else {
-                verbose(format!("[pread] unknown fd {}", fd), config);
-            }
+        if config.strict && RE_SYSCALL.is_match(line) && !RE_COMPLETE.is_match(line) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}:{}: unparseable syscall: {}",
+                    input.display(),
+                    lines.number(),
+                    line
+                ),
+            ));
         }
 
-        for cap in RE_PWRITE.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+        report.seq += 1;
+        report.tid = prefix_pid;
+        report.update_time(time);
+
+        // legacy syscalls count as what they're rewritten to
+        let legacy = legacy(line);
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_write(opsize, bytes);
+        if let Some(cap) = RE_SYSCALL.captures(line) {
+            let syscall = legacy
+                .as_deref()
+                .and_then(|line| RE_SYSCALL.captures(line))
+                .map_or(cap[1].to_string(), |legacy| legacy[1].to_string());
+
+            if SYSCALLS.contains(&syscall.as_str()) || registry.handles(&syscall) {
+                report.handled(&cap[1]);
             } else {
-                verbose(format!("[pwrite] unknown fd {}", fd), config);
+                report.unknown(&cap[1]);
             }
         }
 
-        for cap in RE_READ.captures_iter(&line) {
+        let line = legacy.as_deref().unwrap_or(line);
+        let table = processes.table(owner, fds);
+
+        for cap in RE_IO_ERROR.captures_iter(line) {
             let fd: u32 = cap[1].parse().unwrap();
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_read(opsize, bytes);
-            } else {
-                verbose(format!("[read] unknown fd {}", fd), config);
+            if let Some(summary) = table.get_mut(&fd) {
+                summary.update_error(&cap[2]);
             }
         }
 
-        for cap in RE_SOCKET.captures_iter(&line) {
+        for cap in RE_IO_RESTART.captures_iter(line) {
             let fd: u32 = cap[1].parse().unwrap();
 
-            debug(format!("[socket] {}", fd), config);
+            if let Some(summary) = table.get_mut(&fd) {
+                summary.update_restart();
+            }
+        }
+
+        // threads exit, too, but the status is the one of their process
+        let leads = prefix_pid.is_none() || prefix_pid == processes.root || prefix_pid == group;
 
-            let syscall = "socket";
-            insert(fds, fd, Summary::socket(), syscall, config);
+        for cap in RE_EXIT.captures_iter(line).filter(|_| leads) {
+            report.exit(String::from(&cap[1]));
+            exited = config.follow && prefix_pid == leader;
         }
 
-        for cap in RE_ACCEPT.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
-            let new_fd: u32 = cap[3].parse().unwrap();
+        for cap in RE_CLONE.captures_iter(line) {
+            let syscall = &cap[1];
+            let flags = &cap[2];
+            let pid = &cap[3];
 
-            debug(format!("[accept] {} -> {}", fd, new_fd), config);
+            let trace = Path::new(&input).with_extension(pid);
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                if let GenericFileDescriptor::Socket(_socket_description) = &mut summary.descriptor
-                {
-                    summary.update_accept();
-                    let syscall = "socket";
-                    insert(fds, new_fd, Summary::socket(), syscall, config);
-                } else {
-                    verbose(format!("[accept] called on not a socket fd {}", fd), config);
-                }
-            } else {
-                verbose(format!("[accept] unknown fd {}", fd), config);
+            // then it's in this trace, with -f
+            if !trace.exists() {
+                verbose(
+                    format!("[{}] no trace for pid {} in {:?}", syscall, pid, trace),
+                    config,
+                );
+                processes.clone(prefix_pid, pid.parse().unwrap(), flags, fds);
+                continue;
             }
-        }
 
-        for cap in RE_BIND.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+            verbose(
+                format!("[{}] tracing pid {} in {:?} ...", syscall, pid, trace),
+                config,
+            );
 
-            debug(format!("[bind] {}", fd), config);
+            // a thread's I/O is its process' I/O
+            let child = if flags.contains("CLONE_THREAD") {
+                process.clone()
+            } else {
+                self::pid(&trace)
+            };
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
-                    let bind_addr: String = cap[2].parse().unwrap();
-                    socket_description.update_bind(bind_addr);
-                } else {
-                    verbose(format!("[bind] not a socket fd {}", fd), config);
-                }
+            let fds = processes.table(owner, fds);
+
+            if flags.contains("CLONE_FILES") {
+                self::trace(fds, &trace, child, registry, report, config)?;
             } else {
-                verbose(format!("[bind] unknown fd {}", fd), config);
-            }
-        }
+                let mut cfds = fds.clone();
 
-        for cap in RE_CONNECT.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+                for (_, summary) in cfds.iter_mut() {
+                    summary.reset();
+                }
 
-            debug(format!("[connect] {}", fd), config);
+                let child = self::trace(&mut cfds, &trace, child, registry, report, config)?;
+                let parent = report.enter(child);
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
-                    let connect_addr: String = cap[2].parse().unwrap();
-                    socket_description.update_connect(connect_addr);
-                } else {
-                    verbose(format!("[connect] not a socket fd {}", fd), config);
+                for (_, summary) in cfds.drain() {
+                    report.push(summary, config);
                 }
-            } else {
-                verbose(format!("[connect] unknown fd {}", fd), config);
+
+                report.leave(parent);
             }
-        }
 
-        for cap in RE_RECVFROM.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+            verbose(
+                format!("[{}] tracing pid {} finished", syscall, pid),
+                config,
+            );
+        }
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_read(opsize, bytes);
-            } else {
-                verbose(format!("[read] unknown fd {}", fd), config);
-            }
+        if let Some(cap) = RE_SYSCALL.captures(line) {
+            registry.dispatch(&cap[1], line, processes.table(owner, fds), report, config);
         }
+    }
 
-        for cap in RE_SENDTO.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+    // the traced program's fds are left to the caller, like the shared ones
+    for (owner, fds) in ::std::mem::take(&mut processes.fds) {
+        report.enter(processes.name(processes.leaders[&owner], &process));
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_write(opsize, bytes);
-            } else {
-                verbose(format!("[write] unknown fd {}", fd), config);
-            }
+        for (_, summary) in fds {
+            report.push(summary, config);
         }
+    }
 
-        for cap in RE_WRITE.captures_iter(&line) {
-            let fd: u32 = cap[1].parse().unwrap();
+    report.leave(parent);
 
-            if let Some(summary) = fds.get_mut(&fd) {
-                let opsize: u64 = cap[2].parse().unwrap();
-                let bytes: u64 = cap[3].parse().unwrap();
-                summary.update_write(opsize, bytes);
-            } else {
-                verbose(format!("[write] unknown fd {}", fd), config);
+    Ok(process)
+}
+
+impl Default for Registry {
+    /// Returns a registry with the built-in handlers.
+    fn default() -> Registry {
+        let mut registry = Registry::new();
+
+        registry.register("close", on_close);
+        registry.register("close_range", on_close_range);
+        registry.register("creat", on_creat);
+        registry.register("execve", on_execve);
+        registry.register("dup", on_dup);
+        registry.register("dup2", on_dup2);
+        registry.register("dup3", on_dup2);
+        registry.register("fcntl", on_fcntl);
+        registry.register("fadvise64", on_fadvise);
+        registry.register("fadvise64_64", on_fadvise);
+        registry.register("fstat", on_fstat);
+        registry.register("fstat64", on_fstat);
+        registry.register("newfstatat", on_fstatat);
+        registry.register("fstatat64", on_fstatat);
+        registry.register("statx", on_fstatat);
+        registry.register("fstatfs", on_fstatfs);
+        registry.register("fstatfs64", on_fstatfs);
+        registry.register("statfs", on_statfs);
+        registry.register("statfs64", on_statfs);
+        registry.register("fsync", on_fsync);
+        registry.register("fdatasync", on_fsync);
+        registry.register("io_uring_setup", on_io_uring_setup);
+        registry.register("io_uring_enter", on_io_uring_enter);
+        registry.register("linkat", on_linkat);
+        registry.register("lseek", on_lseek);
+        registry.register("fchmod", on_metadata);
+        registry.register("fchown", on_metadata);
+        registry.register("fchown32", on_metadata);
+        registry.register("fsetxattr", on_metadata);
+        registry.register("utimensat", on_metadata);
+        registry.register("getrandom", on_getrandom);
+        registry.register("memfd_create", on_memfd_create);
+        registry.register("mq_open", on_mq_open);
+        registry.register("mq_timedreceive", on_mq_timedreceive);
+        registry.register("mq_timedsend", on_mq_timedsend);
+        registry.register("open", on_open);
+        registry.register("openat", on_openat);
+        registry.register("openat", on_openat_empty_path);
+        registry.register("open", on_open_unnamed);
+        registry.register("openat", on_open_unnamed);
+        registry.register("pidfd_getfd", on_pidfd_getfd);
+        registry.register("pidfd_open", on_pidfd_open);
+        registry.register("pipe", on_pipe);
+        registry.register("pread", on_pread);
+        registry.register("preadv", on_preadv);
+        registry.register("process_vm_readv", on_process_vm);
+        registry.register("process_vm_writev", on_process_vm);
+        registry.register("pwrite", on_pwrite);
+        registry.register("pwritev", on_pwritev);
+        registry.register("readahead", on_readahead);
+        registry.register("read", on_read);
+        registry.register("readv", on_readv);
+        registry.register("sync", on_sync);
+        registry.register("rename", on_rename);
+        registry.register("renameat", on_renameat);
+        registry.register("renameat2", on_renameat);
+        registry.register("syncfs", on_syncfs);
+        registry.register("socket", on_socket);
+        registry.register("accept", on_accept);
+        registry.register("bind", on_bind);
+        registry.register("connect", on_connect);
+        registry.register("getpeername", on_getpeername);
+        registry.register("getsockname", on_getsockname);
+        registry.register("recvfrom", on_recvfrom);
+        registry.register("sendto", on_sendto);
+        registry.register("recvmmsg", on_recvmmsg);
+        registry.register("sendmmsg", on_sendmmsg);
+        registry.register("write", on_write);
+        registry.register("writev", on_writev);
+        registry.register("vmsplice", on_vmsplice);
+
+        registry
+    }
+}
+
+fn on_close(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_CLOSE: Regex =
+            Regex::new(r#"^close\((\d+)\)\s+= (-?\d+)\s*([A-Z]*).*$"#).unwrap();
+    }
+
+    for cap in RE_CLOSE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let status: i32 = cap[2].parse().unwrap();
+        let error = &cap[3];
+        let syscall = "close";
+
+        match (status, error) {
+            (0, _) => finish(fds, fd, syscall, report, config),
+
+            (_, "EBADF") => debug(format!("[close] {} => bad fd", fd), config),
+
+            (_, error) => {
+                verbose(format!("[close] {} => {}", fd, error), config);
+                finish(fds, fd, syscall, report, config)
             }
         }
     }
+}
 
-    for (_, summary) in fds.iter() {
-        summary.show(config);
+fn on_close_range(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_CLOSE_RANGE: Regex =
+            Regex::new(r#"^close_range\((\d+), (\d+|~0U), (.+)\)\s+= 0$"#).unwrap();
     }
 
-    Ok(())
+    for cap in RE_CLOSE_RANGE.captures_iter(line) {
+        let first: u32 = cap[1].parse().unwrap();
+        let last: u32 = match &cap[2] {
+            "~0U" => u32::MAX,
+            last => last.parse().unwrap(),
+        };
+        let cloexec = cap[3].contains("CLOSE_RANGE_CLOEXEC");
+
+        debug(
+            format!("[close_range] {} - {} cloexec={}", first, last, cloexec),
+            config,
+        );
+
+        let mut range: Vec<u32> = fds
+            .keys()
+            .filter(|fd| first <= **fd && **fd <= last)
+            .cloned()
+            .collect();
+        range.sort();
+
+        for fd in range {
+            if cloexec {
+                if let Some(summary) = fds.get_mut(&fd) {
+                    summary.set_cloexec();
+                }
+            } else {
+                finish(fds, fd, "close_range", report, config);
+            }
+        }
+    }
+}
+
+fn on_creat(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_CREAT: Regex = Regex::new(r#"^creat\("([^"]+)", .+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_CREAT.captures_iter(line) {
+        let file = unescape(&cap[1]);
+        let fd: u32 = cap[2].parse().unwrap();
+
+        debug(format!("[creat] {} => {}", fd, file), config);
+
+        let syscall = "creat";
+        let mut summary = open(fds, file, syscall, config);
+        summary.set_mode(Some(AccessMode::WriteOnly));
+        summary.set_truncated();
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_execve(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_EXECVE: Regex = Regex::new(r#"^execve\(.*\)\s+= 0$"#).unwrap();
+    }
+
+    if RE_EXECVE.is_match(line) {
+        let mut cloexec: Vec<u32> = fds
+            .iter()
+            .filter(|(_, summary)| summary.cloexec)
+            .map(|(fd, _)| *fd)
+            .collect();
+        cloexec.sort();
+
+        for fd in cloexec {
+            finish(fds, fd, "execve", report, config);
+        }
+    }
+}
+
+fn on_dup(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_DUP: Regex = Regex::new(r#"^dup\((\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_DUP.captures_iter(line) {
+        let oldfd: u32 = cap[1].parse().unwrap();
+        let newfd: u32 = cap[2].parse().unwrap();
+
+        dup(fds, "dup", &oldfd, newfd, report, config);
+    }
+}
+
+fn on_dup2(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_DUP2: Regex =
+            Regex::new(r#"^(dup2|dup3)\((\d+), \d+(?:, [^)]+)?\)\s+= (\d+)$"#).unwrap();
+    }
+
+    // also how shells redirect STDOUT and STDERR to a file
+    for cap in RE_DUP2.captures_iter(line) {
+        let syscall = &cap[1];
+        let oldfd: u32 = cap[2].parse().unwrap();
+        let newfd: u32 = cap[3].parse().unwrap();
+
+        dup(fds, syscall, &oldfd, newfd, report, config);
+
+        if line.contains("O_CLOEXEC") {
+            if let Some(summary) = fds.get_mut(&newfd) {
+                summary.set_cloexec();
+            }
+        }
+    }
+}
+
+fn on_fcntl(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_FCNTL_DUP: Regex =
+            Regex::new(r#"^fcntl\((\d+), F_DUPFD, \d+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_FCNTL_SETFL: Regex =
+            Regex::new(r#"^fcntl\((\d+), F_SETFL, (.+)\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_FCNTL_DUP.captures_iter(line) {
+        let oldfd: u32 = cap[1].parse().unwrap();
+        let newfd: u32 = cap[2].parse().unwrap();
+
+        dup(fds, "fcntl-dup", &oldfd, newfd, report, config);
+    }
+
+    for cap in RE_FCNTL_SETFL.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let nonblocking = cap[2].contains("O_NONBLOCK");
+
+        debug(
+            format!("[fcntl] {} nonblocking={}", fd, nonblocking),
+            config,
+        );
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.set_nonblocking(nonblocking);
+        } else {
+            verbose(format!("[fcntl] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_fadvise(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_FADVISE: Regex = Regex::new(
+            r#"^(?:fadvise64|fadvise64_64)\((\d+), \d+, \d+, POSIX_FADV_(\w+)\)\s+= 0$"#
+        )
+        .unwrap();
+    }
+
+    for cap in RE_FADVISE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_advice(&cap[2]);
+        } else {
+            verbose(format!("[fadvise64] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_fstat(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_FSTAT: Regex =
+            Regex::new(r#"^(?:fstat|fstat64)\((\d+), \{.*\bst_size=(\d+).*\}\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_FSTAT.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let size: u64 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_size(size);
+        } else {
+            verbose(format!("[fstat] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_fstatat(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        // stat'ing the fd itself, the empty path only works with AT_EMPTY_PATH
+        static ref RE_FSTATAT: Regex = Regex::new(
+            r#"^(?:newfstatat|fstatat64|statx)\((\d+), "", .*\bstx?_size=(\d+).*\)\s+= 0$"#
+        ).unwrap();
+    }
+
+    for cap in RE_FSTATAT.captures_iter(line) {
+        if !line.contains("AT_EMPTY_PATH") {
+            continue;
+        }
+
+        let fd: u32 = cap[1].parse().unwrap();
+        let size: u64 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_size(size);
+        } else {
+            verbose(format!("[fstatat] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_fstatfs(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        // the 64-bit variant passes the size of the struct first
+        static ref RE_FSTATFS: Regex = Regex::new(
+            r#"^fstatfs(?:64)?\((\d+), (?:\d+, )?\{f_type=(\w+).*\bf_bsize=(\d+).*\}\)\s+= 0$"#
+        )
+        .unwrap();
+    }
+
+    for cap in RE_FSTATFS.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let block_size: u64 = cap[3].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.set_filesystem(&cap[2], block_size);
+        } else {
+            verbose(format!("[fstatfs] unknown fd {}", fd), config);
+        }
+    }
+}
+
+// describes the filesystem of the files open at the path
+fn on_statfs(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_STATFS: Regex = Regex::new(
+            r#"^statfs(?:64)?\("([^"]+)", (?:\d+, )?\{f_type=(\w+).*\bf_bsize=(\d+).*\}\)\s+= 0$"#
+        )
+        .unwrap();
+    }
+
+    for cap in RE_STATFS.captures_iter(line) {
+        let path = unescape(&cap[1]);
+        let block_size: u64 = cap[3].parse().unwrap();
+
+        debug(format!("[statfs] {} => {}", path, &cap[2]), config);
+
+        for summary in fds.values_mut() {
+            if let GenericFileDescriptor::File(file_description) = &summary.descriptor {
+                if file_description.path == path {
+                    summary.set_filesystem(&cap[2], block_size);
+                }
+            }
+        }
+    }
+}
+
+fn on_fsync(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_FSYNC: Regex = Regex::new(r#"^(fsync|fdatasync)\((\d+)\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_FSYNC.captures_iter(line) {
+        let syscall = &cap[1];
+        let fd: u32 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_sync(syscall);
+        } else {
+            verbose(format!("[{}] unknown fd {}", syscall, fd), config);
+        }
+    }
+}
+
+fn on_io_uring_setup(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_IO_URING_SETUP: Regex =
+            Regex::new(r#"^io_uring_setup\(.*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_IO_URING_SETUP.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[io_uring_setup] {}", fd), config);
+
+        let syscall = "io_uring_setup";
+        insert(fds, fd, Summary::io_uring(), syscall, report, config);
+    }
+}
+
+fn on_io_uring_enter(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_IO_URING_ENTER: Regex =
+            Regex::new(r#"^io_uring_enter\((\d+)(?:<[^>]*>)?, .*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_SQE: Regex =
+            Regex::new(r#"^(READ|READV|READ_FIXED|RECV|WRITE|WRITEV|WRITE_FIXED|SEND)\b"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_SQE_FD: Regex = Regex::new(r#"\bfd=(\d+)"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_SQE_LEN: Regex = Regex::new(r#"\blen=(\d+)"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_SQE_OFF: Regex = Regex::new(r#"\boff=(\d+)"#).unwrap();
+    }
+
+    // strace shows the ring fd and how many entries the kernel consumed, the
+    // entries themselves live in shared memory. where they are decoded, e.g.
+    // [{opcode=IORING_OP_READ, fd=3, off=0, len=4096}], the bytes go to the
+    // fds they reference. completions aren't traced, so the requested length
+    // counts as transferred.
+    for cap in RE_IO_URING_ENTER.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let submitted: u64 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_ring_enter(submitted, report.seq);
+        } else {
+            verbose(format!("[io_uring_enter] unknown fd {}", fd), config);
+        }
+
+        let sqes = line
+            .split("{opcode=IORING_OP_")
+            .skip(1)
+            .take(submitted as usize);
+
+        for sqe in sqes {
+            let opcode = match RE_SQE.captures(sqe) {
+                Some(opcode) => opcode,
+                None => continue,
+            };
+
+            let fd = match RE_SQE_FD.captures(sqe) {
+                Some(fd) => fd[1].parse().unwrap(),
+                None => continue,
+            };
+
+            // the vectored ones have the number of iovecs as their length
+            let bytes = if opcode[1].ends_with('V') {
+                iov_len(sqe)
+            } else {
+                RE_SQE_LEN
+                    .captures(sqe)
+                    .map_or(0, |len| len[1].parse().unwrap())
+            };
+
+            let op = match RE_SQE_OFF.captures(sqe) {
+                Some(offset) => Op::at(fd, bytes, bytes, offset[1].parse().unwrap()),
+                None => Op::new(fd, bytes, bytes),
+            };
+
+            match &opcode[1] {
+                "READ" | "READV" | "READ_FIXED" | "RECV" => {
+                    read(fds, op, "io_uring_enter", report, config)
+                }
+                _ => write(fds, op, "io_uring_enter", report, config),
+            }
+        }
+    }
+}
+
+fn on_linkat(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_LINKAT: Regex = Regex::new(
+            r#"^linkat\((\d+|AT_FDCWD), "([^"]*)", (\d+|AT_FDCWD), "([^"]+)", [^)]*\)\s+= 0$"#
+        )
+        .unwrap();
+    }
+
+    // names a file opened with O_TMPFILE, either by its fd or its
+    // /proc/self/fd symlink, or links a named one like a rename would
+    for cap in RE_LINKAT.captures_iter(line) {
+        let path = join_paths(fds, &cap[3], &cap[4]);
+
+        let fd = if cap[2].is_empty() {
+            cap[1].parse().ok()
+        } else {
+            linked_fd(&cap[2])
+        };
+
+        let fd = match fd {
+            Some(fd) => fd,
+            None => {
+                let from = join_paths(fds, &cap[1], &cap[2]);
+                rename(fds, report, &from, &path, false, config);
+                continue;
+            }
+        };
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::File(file_description) = &mut summary.descriptor {
+                if file_description.path.starts_with("(O_TMPFILE in ") {
+                    debug(
+                        format!("[linkat] {} => {}", file_description.path, path),
+                        config,
+                    );
+                    file_description.path = path;
+                }
+            }
+        }
+    }
+}
+
+fn on_lseek(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_LSEEK: Regex =
+            Regex::new(r#"^lseek\((\d+), -?\d+, SEEK_\w+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_LSEEK.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let offset: u64 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_offset(offset);
+        } else {
+            verbose(format!("[lseek] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_metadata(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_METADATA: Regex = Regex::new(
+            r#"^(?:(fchmod|fchown|fchown32|fsetxattr)\((\d+),|utimensat\((\d+), NULL,).*\)\s+= 0$"#
+        )
+        .unwrap();
+    }
+
+    for cap in RE_METADATA.captures_iter(line) {
+        // futimens is utimensat on the fd itself, fchown32 is fchown on 32-bit
+        let (syscall, fd) = match (cap.get(1), cap.get(2), cap.get(3)) {
+            (Some(syscall), Some(fd), _) => (syscall.as_str().trim_end_matches("32"), fd),
+            (_, _, Some(fd)) => ("futimens", fd),
+            _ => unreachable!(),
+        };
+        let fd: u32 = fd.as_str().parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_metadata(syscall);
+        } else {
+            verbose(format!("[{}] unknown fd {}", syscall, fd), config);
+        }
+    }
+}
+
+fn on_getrandom(
+    line: &str,
+    _fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        // the buffer may contain commas, so match the size and flags from the end
+        static ref RE_GETRANDOM: Regex =
+            Regex::new(r#"^getrandom\(.*, (\d+), [^,]+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_GETRANDOM.captures_iter(line) {
+        let opsize: u64 = cap[1].parse().unwrap();
+        let bytes: u64 = cap[2].parse().unwrap();
+
+        report.getrandom(opsize, bytes, config);
+    }
+}
+
+fn on_memfd_create(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_MEMFD_CREATE: Regex =
+            Regex::new(r#"^memfd_create\("([^"]*)", .+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_MEMFD_CREATE.captures_iter(line) {
+        let name = unescape(&cap[1]);
+        let fd: u32 = cap[2].parse().unwrap();
+
+        debug(format!("[memfd_create] {} => {}", fd, name), config);
+
+        let mut summary = Summary::memfd(name);
+        if line.contains("MFD_CLOEXEC") {
+            summary.set_cloexec();
+        }
+
+        insert(fds, fd, summary, "memfd_create", report, config);
+    }
+}
+
+fn on_mq_open(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_MQ_OPEN: Regex =
+            Regex::new(r#"^mq_open\("([^"]+)", .+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_MQ_OPEN.captures_iter(line) {
+        let name = unescape(&cap[1]);
+        let fd: u32 = cap[2].parse().unwrap();
+
+        debug(format!("[mq_open] {} => {}", fd, name), config);
+
+        let syscall = "mq_open";
+        insert(
+            fds,
+            fd,
+            Summary::message_queue(name),
+            syscall,
+            report,
+            config,
+        );
+    }
+}
+
+fn on_mq_timedreceive(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_MQ_TIMEDRECEIVE: Regex = Regex::new(
+            r#"^mq_timedreceive\((\d+), .*, (\d+), (?:\[\d+\]|NULL), (?:NULL|\{[^}]*\})\)\s+= (\d+)$"#
+        ).unwrap();
+    }
+
+    for cap in RE_MQ_TIMEDRECEIVE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        read(
+            fds,
+            Op::new(fd, opsize, bytes),
+            "mq_timedreceive",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_mq_timedsend(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_MQ_TIMEDSEND: Regex =
+            Regex::new(r#"^mq_timedsend\((\d+), .*, (\d+), \d+, (?:NULL|\{[^}]*\})\)\s+= 0$"#)
+                .unwrap();
+    }
+
+    for cap in RE_MQ_TIMEDSEND.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let bytes: u64 = cap[2].parse().unwrap();
+
+        write(
+            fds,
+            Op::new(fd, bytes, bytes),
+            "mq_timedsend",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_open(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_OPEN: Regex = Regex::new(
+            // we're ignoring failures on purpose because they don't open fd
+            r#"^open\("([^"]+)", (.+)\)\s+= (\d+)$"#
+        ).unwrap();
+    }
+
+    for cap in RE_OPEN.captures_iter(line) {
+        let file = tmpfile(unescape(&cap[1]), &cap[2]);
+        let fd: u32 = cap[3].parse().unwrap();
+
+        debug(format!("[open] {} => {}", fd, file), config);
+
+        let syscall = "open";
+        let mut summary = open(fds, file, syscall, config);
+        summary.set_mode(AccessMode::from_flags(&cap[2]));
+        if cap[2].contains("O_TRUNC") {
+            summary.set_truncated();
+        }
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_openat(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_OPENAT: Regex =
+            Regex::new(r#"^openat\((\d+|AT_FDCWD), "([^"]+)", (.+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_OPENAT.captures_iter(line) {
+        let dirfd = &cap[1];
+        let pathname = &cap[2];
+        let fd: u32 = cap[4].parse().unwrap();
+
+        let file = tmpfile(join_paths(fds, dirfd, pathname), &cap[3]);
+
+        debug(format!("[openat] {} => {}", fd, file), config);
+
+        let syscall = "openat";
+        let mut summary = open(fds, file, syscall, config);
+        summary.set_mode(AccessMode::from_flags(&cap[3]));
+        if cap[3].contains("O_TRUNC") {
+            summary.set_truncated();
+        }
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_openat_empty_path(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_OPENAT_EMPTY_PATH: Regex =
+            Regex::new(r#"^openat\((\d+), "", .*AT_EMPTY_PATH.*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    // reopens the file description dirfd refers to
+    for cap in RE_OPENAT_EMPTY_PATH.captures_iter(line) {
+        let dirfd: u32 = cap[1].parse().unwrap();
+        let fd: u32 = cap[2].parse().unwrap();
+
+        dup(fds, "openat", &dirfd, fd, report, config);
+    }
+}
+
+fn on_open_unnamed(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        // strace -y appends the path to the returned fd, e.g. = 3</etc/hosts>
+        static ref RE_OPEN_UNNAMED: Regex = Regex::new(
+            r#"^(open|openat)\((?:(?:\d+|AT_FDCWD)(?:<[^>]*>)?, )?""(?:\.\.\.)?, (.+)\)\s+= (\d+)(?:<([^>]*)>)?$"#
+        ).unwrap();
+    }
+
+    // a blank path, e.g. from a trace with -s 0 where it is cut to ""...,
+    // names nothing
+    for cap in RE_OPEN_UNNAMED.captures_iter(line) {
+        if cap[2].contains("AT_EMPTY_PATH") {
+            continue;
+        }
+
+        let fd: u32 = cap[3].parse().unwrap();
+
+        let file = match cap.get(4) {
+            Some(annotation) => String::from(annotation.as_str()),
+            None => format!("{} (unnamed)", fd),
+        };
+
+        let syscall = &cap[1];
+
+        debug(format!("[{}] {} => {}", syscall, fd, file), config);
+
+        let mut summary = open(fds, file, syscall, config);
+        summary.set_mode(AccessMode::from_flags(&cap[2]));
+        if cap[2].contains("O_TRUNC") {
+            summary.set_truncated();
+        }
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_pidfd_getfd(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_PIDFD_GETFD: Regex =
+            Regex::new(r#"^pidfd_getfd\((\d+), (\d+), [^)]*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PIDFD_GETFD.captures_iter(line) {
+        let pidfd: u32 = cap[1].parse().unwrap();
+        let targetfd: u32 = cap[2].parse().unwrap();
+        let fd: u32 = cap[3].parse().unwrap();
+
+        let pid = match fds.get(&pidfd).map(|summary| &summary.descriptor) {
+            Some(GenericFileDescriptor::Pidfd(pid)) => *pid,
+            _ => {
+                verbose(format!("[pidfd_getfd] unknown pidfd {}", pidfd), config);
+                continue;
+            }
+        };
+
+        debug(
+            format!("[pidfd_getfd] {} => fd {} of pid {}", fd, targetfd, pid),
+            config,
+        );
+
+        let syscall = "pidfd_getfd";
+        let summary = Summary::imported(pid, targetfd);
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_pidfd_open(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_PIDFD_OPEN: Regex =
+            Regex::new(r#"^pidfd_open\((\d+), [^)]*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PIDFD_OPEN.captures_iter(line) {
+        let pid: u32 = cap[1].parse().unwrap();
+        let fd: u32 = cap[2].parse().unwrap();
+
+        debug(format!("[pidfd_open] {} => pid {}", fd, pid), config);
+
+        let syscall = "pidfd_open";
+        insert(fds, fd, Summary::pidfd(pid), syscall, report, config);
+    }
+}
+
+fn on_pipe(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_PIPE: Regex = Regex::new(r#"^pipe\(\[(\d+), (\d+)\]\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PIPE.captures_iter(line) {
+        let readend = cap[1].parse().unwrap();
+        let writeend = cap[2].parse().unwrap();
+
+        debug(format!("[pipe] {} => {}", readend, writeend), config);
+
+        let syscall = "pipe";
+        insert(fds, readend, Summary::pipe(), syscall, report, config);
+        insert(fds, writeend, Summary::pipe(), syscall, report, config);
+    }
+}
+
+fn on_pread(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_PREAD: Regex =
+            Regex::new(r#"^pread\((\d+),.*, (\d+), (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PREAD.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let offset: u64 = cap[3].parse().unwrap();
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        read(
+            fds,
+            Op::at(fd, opsize, bytes, offset),
+            "pread",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_preadv(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_PREADV: Regex =
+            Regex::new(r#"^preadv\((\d+), \[(.*)\], \d+, (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PREADV.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize = iov_len(&cap[2]);
+        let offset: u64 = cap[3].parse().unwrap();
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        read(
+            fds,
+            Op::at(fd, opsize, bytes, offset),
+            "preadv",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_process_vm(
+    line: &str,
+    _fds: &mut HashMap<u32, Summary>,
+    report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_PROCESS_VM: Regex = Regex::new(
+            r#"^process_vm_(readv|writev)\((\d+), \[(.*?)\], \d+, \[.*\], \d+, \d+\)\s+= (\d+)$"#
+        )
+        .unwrap();
+    }
+
+    for cap in RE_PROCESS_VM.captures_iter(line) {
+        let pid: u32 = cap[2].parse().unwrap();
+        let opsize = iov_len(&cap[3]);
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        debug(
+            format!("[process_vm_{}] pid {} => {}", &cap[1], pid, bytes),
+            config,
+        );
+
+        report.update_process_vm(pid, opsize, bytes, &cap[1] == "readv");
+    }
+}
+
+fn on_pwrite(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_PWRITE: Regex =
+            Regex::new(r#"^pwrite\((\d+),.*, (\d+), (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PWRITE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let offset: u64 = cap[3].parse().unwrap();
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        write(
+            fds,
+            Op::at(fd, opsize, bytes, offset),
+            "pwrite",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_pwritev(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_PWRITEV: Regex =
+            Regex::new(r#"^pwritev\((\d+), \[(.*)\], \d+, (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_PWRITEV.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize = iov_len(&cap[2]);
+        let offset: u64 = cap[3].parse().unwrap();
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        write(
+            fds,
+            Op::at(fd, opsize, bytes, offset),
+            "pwritev",
+            report,
+            config,
+        );
+    }
+}
+
+fn on_readahead(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    _report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_READAHEAD: Regex =
+            Regex::new(r#"^readahead\((\d+), \d+, (\d+)\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_READAHEAD.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let count: u64 = cap[2].parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            summary.update_readahead(count);
+        } else {
+            verbose(format!("[readahead] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_read(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_READ: Regex = Regex::new(r#"^read\((\d+),.*, (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_READ.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        read(fds, Op::new(fd, opsize, bytes), "read", report, config);
+    }
+}
+
+fn on_readv(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_READV: Regex =
+            Regex::new(r#"^readv\((\d+), \[(.*)\], \d+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_READV.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize = iov_len(&cap[2]);
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        read(fds, Op::new(fd, opsize, bytes), "readv", report, config);
+    }
+}
+
+fn on_sync(line: &str, _fds: &mut HashMap<u32, Summary>, report: &mut Report, _config: &Config) {
+    lazy_static! {
+        static ref RE_SYNC: Regex = Regex::new(r#"^sync\(\)\s+= 0$"#).unwrap();
+    }
+
+    if RE_SYNC.is_match(line) {
+        report.sync();
+    }
+}
+
+fn on_rename(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_RENAME: Regex =
+            Regex::new(r#"^rename\("([^"]+)", "([^"]+)"\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_RENAME.captures_iter(line) {
+        let from = unescape(&cap[1]);
+        let to = unescape(&cap[2]);
+        rename(fds, report, &from, &to, false, config);
+    }
+}
+
+fn on_renameat(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_RENAMEAT: Regex = Regex::new(
+            r#"^renameat2?\((\d+|AT_FDCWD), "([^"]+)", (\d+|AT_FDCWD), "([^"]+)"(?:, [^)]*)?\)\s+= 0$"#
+        ).unwrap();
+    }
+
+    for cap in RE_RENAMEAT.captures_iter(line) {
+        let from = join_paths(fds, &cap[1], &cap[2]);
+        let to = join_paths(fds, &cap[3], &cap[4]);
+        let exchange = line.contains("RENAME_EXCHANGE");
+
+        rename(fds, report, &from, &to, exchange, config);
+    }
+}
+
+fn on_syncfs(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_SYNCFS: Regex = Regex::new(r#"^syncfs\((\d+)\)\s+= 0$"#).unwrap();
+    }
+
+    // the flush is the filesystem's, not the one of the file which was used
+    // to refer to it, known if the file was statfs'ed
+    for cap in RE_SYNCFS.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        let filesystem = match fds.get(&fd) {
+            Some(summary) => match &summary.descriptor {
+                GenericFileDescriptor::File(file_description) => {
+                    file_description.filesystem.clone()
+                }
+                _ => None,
+            },
+            None => {
+                verbose(format!("[syncfs] unknown fd {}", fd), config);
+                None
+            }
+        };
+
+        report.syncfs(filesystem);
+    }
+}
+
+fn on_socket(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_SOCKET: Regex = Regex::new(r#"^socket\(.*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_SOCKET.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[socket] {}", fd), config);
+
+        let mut summary = Summary::socket();
+        summary.set_nonblocking(line.contains("SOCK_NONBLOCK"));
+
+        let syscall = "socket";
+        insert(fds, fd, summary, syscall, report, config);
+    }
+}
+
+fn on_accept(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_ACCEPT: Regex =
+            Regex::new(r#"^accept\((\d+), (.*), .*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_ACCEPT.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let new_fd: u32 = cap[3].parse().unwrap();
+
+        debug(format!("[accept] {} -> {}", fd, new_fd), config);
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::Socket(_socket_description) = &mut summary.descriptor {
+                summary.update_accept();
+                let syscall = "socket";
+                insert(fds, new_fd, Summary::socket(), syscall, report, config);
+            } else {
+                verbose(format!("[accept] called on not a socket fd {}", fd), config);
+            }
+        } else {
+            verbose(format!("[accept] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_bind(line: &str, fds: &mut HashMap<u32, Summary>, _report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_BIND: Regex =
+            Regex::new(r#"^bind\((\d+), (.*), \d+\)\s+= (\d+).*$"#).unwrap();
+    }
+
+    for cap in RE_BIND.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[bind] {}", fd), config);
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
+                let bind_addr: String = cap[2].parse().unwrap();
+                socket_description.update_bind(bind_addr);
+            } else {
+                verbose(format!("[bind] not a socket fd {}", fd), config);
+            }
+        } else {
+            verbose(format!("[bind] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_connect(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_CONNECT: Regex =
+            // a nonblocking connect completes later, but its endpoint is known
+            Regex::new(r#"^connect\((\d+), (.*), \d+\)\s+= (\d+|-1 EINPROGRESS).*$"#).unwrap();
+    }
+
+    for cap in RE_CONNECT.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[connect] {}", fd), config);
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
+                let connect_addr: String = cap[2].parse().unwrap();
+                socket_description.update_connect(connect_addr);
+                summary.set_connected(report.time);
+            } else {
+                verbose(format!("[connect] not a socket fd {}", fd), config);
+            }
+        } else {
+            verbose(format!("[connect] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_getpeername(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    _report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_GETPEERNAME: Regex =
+            Regex::new(r#"^getpeername\((\d+), (\{.*\}), \[.*\]\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_GETPEERNAME.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[getpeername] {}", fd), config);
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
+                if socket_description.connect().is_empty() {
+                    socket_description.update_connect(String::from(&cap[2]));
+                }
+            } else {
+                verbose(format!("[getpeername] not a socket fd {}", fd), config);
+            }
+        } else {
+            verbose(format!("[getpeername] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_getsockname(
+    line: &str,
+    fds: &mut HashMap<u32, Summary>,
+    _report: &mut Report,
+    config: &Config,
+) {
+    lazy_static! {
+        static ref RE_GETSOCKNAME: Regex =
+            Regex::new(r#"^getsockname\((\d+), (\{.*\}), \[.*\]\)\s+= 0$"#).unwrap();
+    }
+
+    for cap in RE_GETSOCKNAME.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+
+        debug(format!("[getsockname] {}", fd), config);
+
+        if let Some(summary) = fds.get_mut(&fd) {
+            if let GenericFileDescriptor::Socket(socket_description) = &mut summary.descriptor {
+                if socket_description.bind().is_empty() {
+                    socket_description.update_bind(String::from(&cap[2]));
+                }
+            } else {
+                verbose(format!("[getsockname] not a socket fd {}", fd), config);
+            }
+        } else {
+            verbose(format!("[getsockname] unknown fd {}", fd), config);
+        }
+    }
+}
+
+fn on_recvfrom(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_RECVFROM: Regex =
+            Regex::new(r#"^recvfrom\((\d+),.*, (\d+), .*, .*, .*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_RECVFROM.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        read(fds, Op::new(fd, opsize, bytes), "recvfrom", report, config);
+    }
+}
+
+fn on_sendto(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_SENDTO: Regex =
+            Regex::new(r#"^sendto\((\d+),.*, (\d+), .*, .*, .*\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_SENDTO.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        write(fds, Op::new(fd, opsize, bytes), "sendto", report, config);
+    }
+}
+
+fn on_recvmmsg(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_RECVMMSG: Regex =
+            Regex::new(r#"^recvmmsg\((\d+), \[(.*)\], \d+, .+\)\s+= (\d+).*$"#).unwrap();
+    }
+
+    for cap in RE_RECVMMSG.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let count: usize = cap[3].parse().unwrap();
+
+        for (opsize, bytes) in mmsg_lens(&cap[2], count) {
+            read(fds, Op::new(fd, opsize, bytes), "recvmmsg", report, config);
+        }
+    }
+}
+
+fn on_sendmmsg(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_SENDMMSG: Regex =
+            Regex::new(r#"^sendmmsg\((\d+), \[(.*)\], \d+, .+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_SENDMMSG.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let count: usize = cap[3].parse().unwrap();
+
+        for (opsize, bytes) in mmsg_lens(&cap[2], count) {
+            write(fds, Op::new(fd, opsize, bytes), "sendmmsg", report, config);
+        }
+    }
+}
+
+fn on_write(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_WRITE: Regex = Regex::new(r#"^write\((\d+),.*, (\d+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_WRITE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize: u64 = cap[2].parse().unwrap();
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        write(fds, Op::new(fd, opsize, bytes), "write", report, config);
+    }
+}
+
+fn on_writev(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_WRITEV: Regex =
+            Regex::new(r#"^writev\((\d+), \[(.*)\], \d+\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_WRITEV.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize = iov_len(&cap[2]);
+        let bytes: u64 = cap[3].parse().unwrap();
+
+        write(fds, Op::new(fd, opsize, bytes), "writev", report, config);
+    }
+}
+
+fn on_vmsplice(line: &str, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    lazy_static! {
+        static ref RE_VMSPLICE: Regex =
+            Regex::new(r#"^vmsplice\((\d+), \[(.*)\], \d+, (.+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for cap in RE_VMSPLICE.captures_iter(line) {
+        let fd: u32 = cap[1].parse().unwrap();
+        let opsize = iov_len(&cap[2]);
+        let bytes: u64 = cap[4].parse().unwrap();
+
+        if cap[3].contains("SPLICE_F_GIFT") {
+            verbose(
+                format!("[vmsplice] {} => pages gifted to the pipe", fd),
+                config,
+            );
+        }
+
+        write(fds, Op::new(fd, opsize, bytes), "vmsplice", report, config);
+    }
 }
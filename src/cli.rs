@@ -0,0 +1,671 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// the command line interface, turning the arguments and the defaults file
+// into a config
+
+use config::{
+    Config, Defaults, InputFormat, Kind, OutputFormat, SortKey, Unit, DEFAULT_FIELDS, DEFAULT_TIERS,
+};
+use summary::{COLLAPSE_PATTERN, FIELDS};
+
+use atty::{self, Stream};
+use clap::{App, AppSettings, Arg, ArgMatches};
+use regex::Regex;
+
+pub fn app() -> App<'static, 'static> {
+    let color = if atty::is(Stream::Stdout) {
+        AppSettings::ColoredHelp
+    } else {
+        AppSettings::ColorNever
+    };
+
+    App::new("strace-analyzer")
+        .version(crate_version!())
+        .global_setting(color)
+        .about("analyze strace output")
+        .arg(
+            Arg::with_name("file")
+                .help("strace log, - for stdin")
+                .required_unless("load"),
+        )
+        .arg(Arg::with_name("debug").long("debug").help("debug output"))
+        .arg(
+            Arg::with_name("anonymize")
+                .long("anonymize")
+                .help("replace path components with stable hashed tokens in the output"),
+        )
+        .arg(
+            Arg::with_name("by-access-mode")
+                .long("by-access-mode")
+                .help("break the bytes down by the access mode the files were opened with"),
+        )
+        .arg(
+            Arg::with_name("bytes-unit")
+                .long("bytes-unit")
+                .takes_value(true)
+                .value_name("unit")
+                .possible_values(&["B", "K", "M", "G"])
+                .help("render all byte counts in this unit instead of picking one by magnitude"),
+        )
+        .arg(
+            Arg::with_name("collapse-numeric")
+                .long("collapse-numeric")
+                .help("merge rotated files like app.log.1 and app.log.2 into app.log.*"),
+        )
+        .arg(
+            Arg::with_name("collapse-pattern")
+                .long("collapse-pattern")
+                .takes_value(true)
+                .value_name("regex")
+                .requires("collapse-numeric")
+                .help("the suffix telling rotated files apart, defaults to a number or date"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .value_name("when")
+                .possible_values(&["auto", "always", "never"])
+                .default_value("auto")
+                .help("color the histogram bars, drawn on terminals or when always, else plain numbers"),
+        )
+        .arg(
+            Arg::with_name("compact")
+                .long("compact")
+                .help("print one line per fd, e.g. FILE:/data R:10.0M/2500ops W:4.0M/1000ops"),
+        )
+        .arg(
+            Arg::with_name("compact-sizes")
+                .long("compact-sizes")
+                .help("print sizes with as few digits as possible, e.g. 4K, 1.2M or 900B"),
+        )
+        .arg(
+            Arg::with_name("diff")
+                .long("diff")
+                .takes_value(true)
+                .value_name("other")
+                .help("compare per-file I/O against another (baseline) strace log"),
+        )
+        .arg(
+            Arg::with_name("exclude-zero-byte-ops")
+                .long("exclude-zero-byte-ops")
+                .help("don't count writes of zero bytes as ops, e.g. write(1, \"\", 0)"),
+        )
+        .arg(
+            Arg::with_name("fields")
+                .long("fields")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .possible_values(FIELDS)
+                .help("fields and their order for --format csv/json"),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .long("follow")
+                .help("keep reading at the end of the log until the traced program exits"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["text", "csv", "json", "prometheus", "table"])
+                .default_value("text")
+                .help("output format, csv/json/table print one record per fd, prometheus one per path"),
+        )
+        .arg(
+            Arg::with_name("global-histogram")
+                .long("global-histogram")
+                .help("print the reads and writes per op size over all fds combined"),
+        )
+        .arg(
+            Arg::with_name("hot-files")
+                .long("hot-files")
+                .help("start with the files that were read and written the most"),
+        )
+        .arg(
+            Arg::with_name("include")
+                .long("include")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .value_name("glob")
+                .help("only show and total files and sockets whose path or address matches"),
+        )
+        .arg(
+            Arg::with_name("include-zero-io")
+                .long("include-zero-io")
+                .help("also show files which were opened but never read or written"),
+        )
+        .arg(
+            Arg::with_name("input-format")
+                .long("input-format")
+                .takes_value(true)
+                .possible_values(&["strace", "ltrace", "runsc", "json-events"])
+                .default_value("strace")
+                .help("format of the input log, runsc for gVisor strace debug logs"),
+        )
+        .arg(
+            Arg::with_name("lenient")
+                .long("lenient")
+                .help("skip lines that are not strace output, e.g. interleaved program output"),
+        )
+        .arg(
+            Arg::with_name("limit-lines")
+                .long("limit-lines")
+                .takes_value(true)
+                .value_name("n")
+                .help("analyze only the first n lines of the log as a quick sample"),
+        )
+        .arg(
+            Arg::with_name("load")
+                .long("load")
+                .takes_value(true)
+                .value_name("summaries")
+                .conflicts_with("file")
+                .help("render summaries saved with --save instead of analyzing a log"),
+        )
+        .arg(
+            Arg::with_name("max-descriptors")
+                .long("max-descriptors")
+                .takes_value(true)
+                .value_name("n")
+                .help(
+                    "keep at most this many open descriptors per process, evicting idle ones early",
+                ),
+        )
+        .arg(
+            Arg::with_name("max-labels")
+                .long("max-labels")
+                .takes_value(true)
+                .value_name("n")
+                .help("fold the paths beyond the n busiest into one prometheus series per kind"),
+        )
+        .arg(
+            Arg::with_name("max-line-length")
+                .long("max-line-length")
+                .takes_value(true)
+                .value_name("bytes")
+                .default_value("16777216")
+                .help("skip trace lines longer than this"),
+        )
+        .arg(
+            Arg::with_name("merge-sockets-by-endpoint")
+                .long("merge-sockets-by-endpoint")
+                .help("sum the I/O of all sockets connected to the same address, e.g. a pool"),
+        )
+        .arg(
+            Arg::with_name("metadata")
+                .long("metadata")
+                .help("report fchmod/fchown/fsetxattr/futimens calls per file"),
+        )
+        .arg(
+            Arg::with_name("no-default-filter")
+                .long("no-default-filter")
+                .help("show files below /usr/, /proc/, /tmp/ etc. which are hidden by default"),
+        )
+        .arg(
+            Arg::with_name("no-pipes")
+                .long("no-pipes")
+                .help("hide pipes from the output and the totals"),
+        )
+        .arg(
+            Arg::with_name("no-wrap")
+                .long("no-wrap")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .value_name("width")
+                .overrides_with("wrap")
+                .validator(|width| match width.parse::<usize>() {
+                    Ok(width) if width > 0 => Ok(()),
+                    _ => Err(format!("invalid width: {}", width)),
+                })
+                .help("shorten long paths in the middle to fit the terminal or width columns"),
+        )
+        .arg(
+            Arg::with_name("no-sockets")
+                .long("no-sockets")
+                .help("hide sockets from the output and the totals"),
+        )
+        .arg(
+            Arg::with_name("note-special-devices")
+                .long("note-special-devices")
+                .help("show /dev/null and /dev/zero, labeled as discarded/synthetic data"),
+        )
+        .arg(
+            Arg::with_name("only")
+                .long("only")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .use_delimiter(true)
+                .possible_values(&["files", "sockets", "pipes"])
+                .help("only show and total these kinds of descriptors"),
+        )
+        .arg(
+            Arg::with_name("op-sizes")
+                .long("op-sizes")
+                .help("print min/avg/max requested op sizes per fd and the ops of each size"),
+        )
+        .arg(
+            Arg::with_name("per-tid")
+                .long("per-tid")
+                .help("show which thread did how much I/O, for traces with [pid N] prefixes"),
+        )
+        .arg(
+            Arg::with_name("percent")
+                .long("percent")
+                .help("show each fd's read and write bytes as a share of the run total"),
+        )
+        .arg(
+            Arg::with_name("pid-overview")
+                .long("pid-overview")
+                .help("print one line per process before the per-fd output"),
+        )
+        .arg(
+            Arg::with_name("precision")
+                .long("precision")
+                .takes_value(true)
+                .value_name("digits")
+                .requires("bytes-unit")
+                .help("digits after the decimal point with --bytes-unit, 3 by default"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("suppress all diagnostics, only print the summaries"),
+        )
+        .arg(
+            Arg::with_name("ratio")
+                .long("ratio")
+                .help("print the read:write byte ratio per file, all its fds combined"),
+        )
+        .arg(
+            Arg::with_name("relative-paths")
+                .long("relative-paths")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .value_name("base")
+                .help("strip the directory all shown files share, or base, from text/table paths"),
+        )
+        .arg(
+            Arg::with_name("resolve-ports")
+                .long("resolve-ports")
+                .help("show well-known socket ports by service name, e.g. https instead of 443"),
+        )
+        .arg(
+            Arg::with_name("retry-threshold")
+                .long("retry-threshold")
+                .takes_value(true)
+                .value_name("percent")
+                .default_value("50")
+                .help("warn about fds where more I/O attempts failed with EINTR/EAGAIN"),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .long("sample")
+                .takes_value(true)
+                .value_name("1/k")
+                .validator(|rate| sample(&rate).map(|_| ()))
+                .help("analyze only every kth line of the log as a quick sample"),
+        )
+        .arg(
+            Arg::with_name("save")
+                .long("save")
+                .takes_value(true)
+                .value_name("summaries")
+                .help("save the summaries as JSON lines for later use with --load"),
+        )
+        .arg(
+            Arg::with_name("score")
+                .long("score")
+                .help("rate the I/O of each file from 0 (poor) to 100 (well-formed)"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .value_name("key")
+                .possible_values(&["closed", "bytes", "ops"])
+                .default_value("closed")
+                .help("order fds as closed, by bytes for throughput or by ops for overhead"),
+        )
+        .arg(
+            Arg::with_name("storms")
+                .long("storms")
+                .takes_value(true)
+                .value_name("opens")
+                .help("flag directories with at least this many opens with little or no I/O"),
+        )
+        .arg(
+            Arg::with_name("strict")
+                .long("strict")
+                .conflicts_with("lenient")
+                .help("abort on syscall lines that can't be parsed, e.g. truncated ones"),
+        )
+        .arg(
+            Arg::with_name("summary-by-syscall")
+                .long("summary-by-syscall")
+                .help("total the bytes transferred per syscall, e.g. read vs readv vs pread"),
+        )
+        .arg(
+            Arg::with_name("sync")
+                .long("sync")
+                .help("report fsync/fdatasync calls per file, syncfs per filesystem and global syncs"),
+        )
+        .arg(
+            Arg::with_name("syscall-stats")
+                .long("syscall-stats")
+                .help("tally how often each syscall with a handler was seen"),
+        )
+        .arg(
+            Arg::with_name("tiers")
+                .long("tiers")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .value_name("small,medium,large")
+                .validator(|thresholds| tiers(&thresholds).map(|_| ()))
+                .help("count files by mean op size: tiny, small (512B), medium (4K), large (64K)"),
+        )
+        .arg(
+            Arg::with_name("timeseries")
+                .long("timeseries")
+                .takes_value(true)
+                .value_name("seconds")
+                .help("print read/write bytes per time interval as CSV, needs -t/-tt/-ttt"),
+        )
+        .arg(
+            Arg::with_name("top-errors")
+                .long("top-errors")
+                .takes_value(true)
+                .value_name("n")
+                .help("start with the n files and sockets with the most failed syscalls"),
+        )
+        .arg(
+            Arg::with_name("transfer-ratio")
+                .long("transfer-ratio")
+                .help("print transferred/requested bytes per fd, low for short reads/writes"),
+        )
+        .arg(
+            Arg::with_name("ttys")
+                .long("ttys")
+                .help("show terminal I/O, hidden like the standard streams by default"),
+        )
+        .arg(
+            Arg::with_name("unknown-syscalls")
+                .long("unknown-syscalls")
+                .help("tally syscalls that have no handler"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("verbose output"),
+        )
+        .arg(
+            Arg::with_name("with-index")
+                .long("with-index")
+                .help("print the rows and bytes per kind ahead of the text or table output"),
+        )
+        .arg(
+            Arg::with_name("wrap")
+                .long("wrap")
+                .overrides_with("no-wrap")
+                .help("print paths in full, even past the terminal width (default)"),
+        )
+}
+
+/// Parses the command line `args`, including the program name. Options from
+/// the defaults file go before the command line ones, unless the command line
+/// already sets them.
+pub fn matches(defaults: &Defaults, args: &[String]) -> ArgMatches<'static> {
+    let matches = app().get_matches_from(args);
+
+    if defaults.args(|_| false).is_empty() {
+        return matches;
+    }
+
+    let mut with_defaults: Vec<String> = args.iter().take(1).cloned().collect();
+    with_defaults.extend(defaults.args(|name| matches.occurrences_of(name) != 0));
+    with_defaults.extend(args.iter().skip(1).cloned());
+
+    app().get_matches_from(with_defaults)
+}
+
+/// The configuration from the parsed arguments, with `columns` being the
+/// width of the terminal.
+pub fn config(matches: &ArgMatches, columns: usize) -> Config {
+    Config {
+        anonymize: matches.is_present("anonymize"),
+        by_access_mode: matches.is_present("by-access-mode"),
+        bytes_unit: matches.value_of("bytes-unit").map(|unit| match unit {
+            "B" => Unit::B,
+            "K" => Unit::K,
+            "M" => Unit::M,
+            _ => Unit::G,
+        }),
+        collapse: if matches.is_present("collapse-numeric") {
+            let pattern = matches
+                .value_of("collapse-pattern")
+                .unwrap_or(COLLAPSE_PATTERN);
+            Some(
+                Regex::new(pattern)
+                    .unwrap_or_else(|e| clap::Error::value_validation_auto(e.to_string()).exit()),
+            )
+        } else {
+            None
+        },
+        bars: matches.value_of("color") == Some("always") || atty::is(Stream::Stdout),
+        color: match matches.value_of("color") {
+            Some("always") => true,
+            Some("never") => false,
+            _ => atty::is(Stream::Stdout),
+        },
+        columns,
+        compact: matches.is_present("compact"),
+        compact_sizes: matches.is_present("compact-sizes"),
+        debug: matches.is_present("debug"),
+        verbose: matches.is_present("verbose"),
+        only: matches
+            .values_of("only")
+            .map(|kinds| {
+                kinds
+                    .map(|kind| match kind {
+                        "files" => Kind::Files,
+                        "sockets" => Kind::Sockets,
+                        _ => Kind::Pipes,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        op_sizes: matches.is_present("op-sizes"),
+        sort: match matches.value_of("sort") {
+            Some("bytes") => SortKey::Bytes,
+            Some("ops") => SortKey::Ops,
+            _ => SortKey::Closed,
+        },
+        storms: if matches.is_present("storms") {
+            Some(value_t!(matches, "storms", u64).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        strict: matches.is_present("strict"),
+        summary_by_syscall: matches.is_present("summary-by-syscall"),
+        sync: matches.is_present("sync"),
+        syscall_stats: matches.is_present("syscall-stats"),
+        per_tid: matches.is_present("per-tid"),
+        percent: matches.is_present("percent"),
+        pid_overview: matches.is_present("pid-overview"),
+        precision: if matches.is_present("precision") {
+            value_t!(matches, "precision", usize).unwrap_or_else(|e| e.exit())
+        } else {
+            3
+        },
+        quiet: matches.is_present("quiet"),
+        ratio: matches.is_present("ratio"),
+        relative_base: matches.value_of("relative-paths").map(String::from),
+        relative_paths: matches.is_present("relative-paths"),
+        resolve_ports: matches.is_present("resolve-ports"),
+        sample: matches.value_of("sample").map(|rate| sample(rate).unwrap()),
+        score: matches.is_present("score"),
+        retry_threshold: value_t!(matches, "retry-threshold", f64).unwrap_or_else(|e| e.exit()),
+        tiers: if matches.is_present("tiers") {
+            Some(
+                matches
+                    .value_of("tiers")
+                    .map_or(Ok(DEFAULT_TIERS), tiers)
+                    .unwrap(),
+            )
+        } else {
+            None
+        },
+        timeseries: if matches.is_present("timeseries") {
+            Some(value_t!(matches, "timeseries", f64).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        transfer_ratio: matches.is_present("transfer-ratio"),
+        ttys: matches.is_present("ttys"),
+        top_errors: if matches.is_present("top-errors") {
+            Some(value_t!(matches, "top-errors", usize).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        input_format: match matches.value_of("input-format") {
+            Some("ltrace") => InputFormat::Ltrace,
+            Some("runsc") => InputFormat::Runsc,
+            Some("json-events") => InputFormat::JsonEvents,
+            _ => InputFormat::Strace,
+        },
+        fields: matches
+            .values_of("fields")
+            .map(|fields| fields.map(String::from).collect())
+            .unwrap_or_else(|| {
+                DEFAULT_FIELDS
+                    .iter()
+                    .map(|field| String::from(*field))
+                    .collect()
+            }),
+        exclude_zero_byte_ops: matches.is_present("exclude-zero-byte-ops"),
+        follow: matches.is_present("follow"),
+        format: match matches.value_of("format") {
+            Some("csv") => OutputFormat::Csv,
+            Some("json") => OutputFormat::Json,
+            Some("prometheus") => OutputFormat::Prometheus,
+            Some("table") => OutputFormat::Table,
+            _ => OutputFormat::Text,
+        },
+        global_histogram: matches.is_present("global-histogram"),
+        hot_files: matches.is_present("hot-files"),
+        include: matches
+            .values_of("include")
+            .map(|patterns| patterns.map(String::from).collect())
+            .unwrap_or_default(),
+        include_zero_io: matches.is_present("include-zero-io"),
+        lenient: matches.is_present("lenient"),
+        limit_lines: if matches.is_present("limit-lines") {
+            Some(value_t!(matches, "limit-lines", u64).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        max_descriptors: if matches.is_present("max-descriptors") {
+            Some(value_t!(matches, "max-descriptors", usize).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        max_labels: if matches.is_present("max-labels") {
+            Some(value_t!(matches, "max-labels", usize).unwrap_or_else(|e| e.exit()))
+        } else {
+            None
+        },
+        max_line_length: value_t!(matches, "max-line-length", usize).unwrap_or_else(|e| e.exit()),
+        merge_sockets: matches.is_present("merge-sockets-by-endpoint"),
+        metadata: matches.is_present("metadata"),
+        no_default_filter: matches.is_present("no-default-filter"),
+        no_pipes: matches.is_present("no-pipes"),
+        no_sockets: matches.is_present("no-sockets"),
+        note_special_devices: matches.is_present("note-special-devices"),
+        path_width: if matches.is_present("no-wrap") {
+            Some(
+                matches
+                    .value_of("no-wrap")
+                    .map_or(columns, |width| width.parse().unwrap()),
+            )
+        } else {
+            None
+        },
+        unknown_syscalls: matches.is_present("unknown-syscalls"),
+        with_index: matches.is_present("with-index"),
+    }
+}
+
+// the k of a 1/k sampling rate
+fn sample(rate: &str) -> Result<u64, String> {
+    match rate.trim_start_matches("1/").parse() {
+        Ok(k) if k != 0 => Ok(k),
+        _ => Err(format!("expected a rate like 1/10, not {}", rate)),
+    }
+}
+
+// the op sizes starting the small, medium and large tiers, e.g. 1K,16K,1M
+fn tiers(thresholds: &str) -> Result<[u64; 3], String> {
+    let error = || {
+        format!(
+            "expected three increasing sizes like 1K,16K,1M, not {}",
+            thresholds
+        )
+    };
+
+    let sizes = thresholds
+        .split(',')
+        .map(|size| {
+            let (digits, shift) = match size.trim().to_uppercase() {
+                size if size.ends_with('K') => (size.trim_end_matches('K').to_string(), 10),
+                size if size.ends_with('M') => (size.trim_end_matches('M').to_string(), 20),
+                size if size.ends_with('G') => (size.trim_end_matches('G').to_string(), 30),
+                size => (size.trim_end_matches('B').to_string(), 0),
+            };
+
+            digits
+                .parse::<u64>()
+                .map(|n| n << shift)
+                .map_err(|_| error())
+        })
+        .collect::<Result<Vec<u64>, String>>()?;
+
+    match sizes[..] {
+        [small, medium, large] if 0 < small && small < medium && medium < large => {
+            Ok([small, medium, large])
+        }
+        _ => Err(error()),
+    }
+}
@@ -23,7 +23,511 @@
  *                                                                           *
  * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
+use summary::GenericFileDescriptor;
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub const DEFAULTS_FILE: &str = ".strace-analyzer.toml";
+
+pub const DEFAULT_FIELDS: &[&str] = &[
+    "kind",
+    "path",
+    "read_bytes",
+    "read_ops",
+    "write_bytes",
+    "write_ops",
+];
+
+// the mean op sizes at which files stop being tiny, small and medium
+pub const DEFAULT_TIERS: [u64; 3] = [512, 4096, 65536];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    Strace,
+    Ltrace,
+    Runsc,
+    JsonEvents,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+    Prometheus,
+    Table,
+}
+
+/// The order of the summaries: as the descriptors were closed, by bytes read
+/// and written for throughput, or by read and write syscalls for overhead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    Closed,
+    Bytes,
+    Ops,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    B,
+    K,
+    M,
+    G,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Files,
+    Sockets,
+    Pipes,
+    Other,
+}
+
 pub struct Config {
+    pub anonymize: bool,
+    pub by_access_mode: bool,
+    pub bytes_unit: Option<Unit>,
+    pub collapse: Option<Regex>,
+    /// draw histogram bars, `columns` wide at most
+    pub bars: bool,
+    /// color the bars
+    pub color: bool,
+    pub columns: usize,
+    pub compact: bool,
+    pub compact_sizes: bool,
     pub debug: bool,
     pub verbose: bool,
+    pub per_tid: bool,
+    pub percent: bool,
+    pub pid_overview: bool,
+    pub precision: usize,
+    pub quiet: bool,
+    pub ratio: bool,
+    pub relative_base: Option<String>,
+    pub relative_paths: bool,
+    pub resolve_ports: bool,
+    pub retry_threshold: f64,
+    pub sample: Option<u64>,
+    pub score: bool,
+    pub tiers: Option<[u64; 3]>,
+    pub timeseries: Option<f64>,
+    pub ttys: bool,
+    pub top_errors: Option<usize>,
+    pub transfer_ratio: bool,
+    pub exclude_zero_byte_ops: bool,
+    pub fields: Vec<String>,
+    pub follow: bool,
+    pub format: OutputFormat,
+    pub global_histogram: bool,
+    pub hot_files: bool,
+    pub input_format: InputFormat,
+    pub include: Vec<String>,
+    pub include_zero_io: bool,
+    pub lenient: bool,
+    pub limit_lines: Option<u64>,
+    pub max_descriptors: Option<usize>,
+    pub max_labels: Option<usize>,
+    pub max_line_length: usize,
+    pub merge_sockets: bool,
+    pub metadata: bool,
+    pub no_default_filter: bool,
+    pub no_pipes: bool,
+    pub no_sockets: bool,
+    pub note_special_devices: bool,
+    pub only: Vec<Kind>,
+    pub op_sizes: bool,
+    /// shorten longer paths in the text output to this many columns, keeping
+    /// both ends, e.g. `/very/long/.../file.log`
+    pub path_width: Option<usize>,
+    pub sort: SortKey,
+    pub storms: Option<u64>,
+    pub strict: bool,
+    pub summary_by_syscall: bool,
+    pub sync: bool,
+    pub syscall_stats: bool,
+    pub unknown_syscalls: bool,
+    pub with_index: bool,
+}
+
+// the same defaults as the command line
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            anonymize: false,
+            by_access_mode: false,
+            bytes_unit: None,
+            collapse: None,
+            bars: false,
+            color: false,
+            columns: 80,
+            compact: false,
+            compact_sizes: false,
+            debug: false,
+            verbose: false,
+            per_tid: false,
+            percent: false,
+            pid_overview: false,
+            precision: 3,
+            quiet: false,
+            ratio: false,
+            relative_base: None,
+            relative_paths: false,
+            resolve_ports: false,
+            retry_threshold: 50.0,
+            sample: None,
+            score: false,
+            tiers: None,
+            timeseries: None,
+            ttys: false,
+            top_errors: None,
+            transfer_ratio: false,
+            exclude_zero_byte_ops: false,
+            fields: DEFAULT_FIELDS
+                .iter()
+                .map(|field| String::from(*field))
+                .collect(),
+            follow: false,
+            format: OutputFormat::Text,
+            global_histogram: false,
+            hot_files: false,
+            input_format: InputFormat::Strace,
+            include: Vec::new(),
+            include_zero_io: false,
+            lenient: false,
+            limit_lines: None,
+            max_descriptors: None,
+            max_labels: None,
+            max_line_length: 16 * 1024 * 1024,
+            merge_sockets: false,
+            metadata: false,
+            no_default_filter: false,
+            no_pipes: false,
+            no_sockets: false,
+            note_special_devices: false,
+            path_width: None,
+            only: Vec::new(),
+            op_sizes: false,
+            sort: SortKey::Closed,
+            storms: None,
+            strict: false,
+            summary_by_syscall: false,
+            sync: false,
+            syscall_stats: false,
+            unknown_syscalls: false,
+            with_index: false,
+        }
+    }
+}
+
+impl Config {
+    /// Whether summaries of `descriptor` are reported and counted at all, as
+    /// restricted by `--only`, `--no-pipes` and `--no-sockets`.
+    ///
+    /// ```
+    /// use strace_analyzer::config::Config;
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let config = Config {
+    ///     no_pipes: true,
+    ///     no_sockets: true,
+    ///     ..Config::default()
+    /// };
+    ///
+    /// assert!(config.selects(&Summary::file(String::from("/srv/data")).descriptor));
+    /// assert!(!config.selects(&Summary::pipe().descriptor));
+    /// assert!(!config.selects(&Summary::socket().descriptor));
+    /// ```
+    ///
+    /// The kinds are selected first, then `--include` keeps the files whose
+    /// path and the sockets whose address matches any of its patterns, e.g.
+    /// `10.0.0.1:5432` or the path of a unix socket. Descriptors without
+    /// either, like pipes or sockets that never connected, don't match.
+    ///
+    /// ```
+    /// use strace_analyzer::config::{Config, Kind};
+    /// use strace_analyzer::summary::{GenericFileDescriptor, Summary};
+    ///
+    /// let socket = |address: &str| {
+    ///     let mut socket = Summary::socket();
+    ///     if let GenericFileDescriptor::Socket(socket_description) = &mut socket.descriptor {
+    ///         socket_description.update_connect(String::from(address));
+    ///     }
+    ///     socket.descriptor
+    /// };
+    ///
+    /// let inet = |port| {
+    ///     format!("{{sa_family=AF_INET, sin_port=htons({}), sin_addr=inet_addr(\"10.0.0.1\")}}", port)
+    /// };
+    /// let postgres = socket(&inet(5432));
+    /// let https = socket(&inet(443));
+    /// let unix = socket("{sa_family=AF_UNIX, sun_path=\"/run/app.sock\"}");
+    /// let file = Summary::file(String::from("/run/app.sock")).descriptor;
+    ///
+    /// let config = Config {
+    ///     only: vec![Kind::Sockets],
+    ///     include: vec![String::from("*:5432"), String::from("*.sock")],
+    ///     ..Config::default()
+    /// };
+    ///
+    /// assert!(config.selects(&postgres));
+    /// assert!(config.selects(&unix));
+    /// assert!(!config.selects(&https));
+    /// assert!(!config.selects(&file));
+    /// assert!(!config.selects(&Summary::socket().descriptor));
+    ///
+    /// // all kinds without --only
+    /// let config = Config { only: Vec::new(), ..config };
+    /// assert!(config.selects(&file));
+    /// assert!(!config.selects(&Summary::pipe().descriptor));
+    /// ```
+    pub fn selects(&self, descriptor: &GenericFileDescriptor) -> bool {
+        let kind = match descriptor.kind() {
+            Kind::Pipes if self.no_pipes => false,
+            Kind::Sockets if self.no_sockets => false,
+            kind => self.only.is_empty() || self.only.contains(&kind),
+        };
+
+        if !kind || self.include.is_empty() {
+            return kind;
+        }
+
+        let name = match descriptor {
+            GenericFileDescriptor::File(file_description) => Some(file_description.path.clone()),
+            GenericFileDescriptor::Socket(socket_description) => socket_description.address(),
+            _ => None,
+        };
+
+        name.is_some_and(|name| self.include.iter().any(|pattern| glob(pattern, &name)))
+    }
+}
+
+// whether `text` matches `pattern`, with * for any and ? for a single char
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // let the last * swallow one more char
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Default options read from `.strace-analyzer.toml`, keyed by long option name.
+///
+/// Only flat `key = value` pairs are supported: strings, numbers, booleans and
+/// arrays of strings.
+///
+/// ```
+/// use strace_analyzer::config::Defaults;
+///
+/// let defaults = Defaults::parse("
+///     only = [\"files\", \"sockets\"]
+///     storms = 50 # opens per directory
+///     verbose = true
+///     ratio = false
+/// ").unwrap();
+///
+/// assert_eq!(defaults.value("storms"), Some("50"));
+/// assert_eq!(defaults.value("only"), Some("files,sockets"));
+/// assert_eq!(
+///     defaults.args(|_| false),
+///     vec!["--only=files,sockets", "--storms=50", "--verbose"],
+/// );
+/// assert_eq!(defaults.args(|name| name == "storms"), vec!["--only=files,sockets", "--verbose"]);
+/// ```
+///
+/// The values are attached with `=`, so that none of them can be taken for an
+/// option of its own. Command line options take precedence:
+///
+/// ```
+/// use strace_analyzer::cli;
+/// use strace_analyzer::config::{Defaults, Kind};
+///
+/// let defaults = Defaults::parse("
+///     only = [\"files\"]
+///     storms = 50
+///     ratio = true
+/// ").unwrap();
+///
+/// let args: Vec<String> = vec!["strace-analyzer", "--storms", "10", "app.strace"]
+///     .into_iter()
+///     .map(String::from)
+///     .collect();
+///
+/// let config = cli::config(&cli::matches(&defaults, &args), 80);
+///
+/// assert_eq!(config.only, vec![Kind::Files]);
+/// assert_eq!(config.storms, Some(10));
+/// assert!(config.ratio);
+/// ```
+#[derive(Debug, Default)]
+pub struct Defaults {
+    values: BTreeMap<String, String>,
+}
+
+impl Defaults {
+    /// Reads the defaults file from the home directory, then from the current
+    /// directory, with the latter taking precedence.
+    pub fn load() -> io::Result<Defaults> {
+        let mut defaults = Defaults::default();
+
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        if let Some(home) = env::var_os("HOME") {
+            candidates.push(PathBuf::from(home).join(DEFAULTS_FILE));
+        }
+        candidates.push(PathBuf::from(DEFAULTS_FILE));
+
+        for path in candidates {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            let parsed = Defaults::parse(&content).map_err(|message| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), message),
+                )
+            })?;
+
+            defaults.values.extend(parsed.values);
+        }
+
+        Ok(defaults)
+    }
+
+    pub fn parse(content: &str) -> Result<Defaults, String> {
+        let mut values = BTreeMap::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |message: &str| format!("line {}: {}", number + 1, message);
+
+            if line.starts_with('[') && !line.contains('=') {
+                return Err(invalid("tables are not supported"));
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| invalid("expected key = value"))?;
+
+            if key.is_empty() {
+                return Err(invalid("missing key"));
+            }
+
+            let value = parse_value(value.trim()).ok_or_else(|| invalid("unsupported value"))?;
+
+            values.insert(String::from(key), value);
+        }
+
+        Ok(Defaults { values })
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Renders the defaults as command line arguments, skipping options for
+    /// which `given` reports that the command line already has them.
+    pub fn args<F>(&self, given: F) -> Vec<String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut args = Vec::new();
+
+        for (key, value) in &self.values {
+            if given(key) {
+                continue;
+            }
+
+            match value.as_str() {
+                "false" => {}
+                "true" => args.push(format!("--{}", key)),
+                value => args.push(format!("--{}={}", key, value)),
+            }
+        }
+
+        args
+    }
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    // trailing comments, as long as they're not inside a string
+    let mut in_string = false;
+    let mut end = value.len();
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value = value[..end].trim();
+
+    if value.starts_with('[') && value.ends_with(']') {
+        let items: Option<Vec<String>> = value[1..value.len() - 1]
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_string)
+            .collect();
+
+        return items.map(|items| items.join(","));
+    }
+
+    if value.starts_with('"') {
+        return parse_string(value);
+    }
+
+    if value == "true" || value == "false" || value.parse::<f64>().is_ok() {
+        return Some(String::from(value));
+    }
+
+    None
+}
+
+fn parse_string(value: &str) -> Option<String> {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return None;
+    }
+
+    let inner = &value[1..value.len() - 1];
+    if inner.contains('"') || inner.contains('\\') {
+        return None;
+    }
+
+    Some(String::from(inner))
 }
@@ -0,0 +1,176 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use analysis::{pid, standard_fds};
+use config::Config;
+use json::{self, Value};
+use log::*;
+use reader::{self, Lines};
+use report::Report;
+use summary::Summary;
+
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+/// Analyzes a log of I/O events as written by tracers other than strace,
+/// e.g. DTrace or bpftrace scripts, one JSON object per line:
+///
+/// - `op`: `open`, `read`, `write` or `close`
+/// - `fd`: the file descriptor
+/// - `pid`: the process, defaults to the one the log is named after
+/// - `path`: the file an `open` names, reads and writes of an fd without an
+///   `open` may give it as well
+/// - `bytes`: the bytes transferred by a `read` or `write`
+/// - `size`: the bytes requested, defaults to `bytes`
+/// - `offset`: for positioned I/O like `pread` and `pwrite`
+/// - `time`: seconds since the epoch
+///
+/// Each process starts with its standard streams open, like with strace.
+pub fn analyze(input: &Path, report: &mut Report, config: &Config) -> io::Result<()> {
+    let file = reader::open(input, config)?;
+    let default_pid = pid(input);
+    let parent = report.enter(default_pid.clone());
+
+    let mut processes: HashMap<String, HashMap<u32, Summary>> = HashMap::new();
+    let mut lines = Lines::new(BufReader::new(file), config);
+
+    while let Some(line) = lines.next() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = match json::parse(&line) {
+            Ok(event) => event,
+            Err(_) if config.lenient => {
+                debug(format!("[lenient] skipping {}", line), config);
+                report.skip();
+                continue;
+            }
+            Err(message) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", input.display(), lines.number(), message),
+                ))
+            }
+        };
+
+        let process = match event.get("pid").and_then(Value::as_u64) {
+            Some(pid) => pid.to_string(),
+            None => default_pid.clone(),
+        };
+
+        report.enter(process.clone());
+        report.seq += 1;
+        report.tid = process.parse().ok();
+        report.update_time(event.get("time").and_then(Value::as_f64));
+
+        let fds = processes.entry(process).or_insert_with(standard_fds);
+        handle(&event, fds, report, config);
+    }
+
+    for (process, mut fds) in processes {
+        report.enter(process);
+
+        for (_, summary) in fds.drain() {
+            report.push(summary, config);
+        }
+    }
+
+    report.leave(parent);
+
+    Ok(())
+}
+
+fn handle(event: &Value, fds: &mut HashMap<u32, Summary>, report: &mut Report, config: &Config) {
+    let op = event.get("op").and_then(Value::as_str).unwrap_or("");
+    let path = event.get("path").and_then(Value::as_str).map(String::from);
+    let number = |key| event.get(key).and_then(Value::as_u64);
+
+    let fd = match number("fd") {
+        Some(fd) => fd as u32,
+        None => {
+            verbose(format!("[{}] event without fd", op), config);
+            return;
+        }
+    };
+
+    match op {
+        "open" => {
+            let path = path.unwrap_or_else(|| String::from("(unnamed)"));
+            debug(format!("[open] {} => {}", fd, path), config);
+
+            let mut summary = Summary::file(path);
+            summary.set_opened(report.time);
+
+            if let Some(summary) = fds.insert(fd, summary) {
+                report.push(summary, config);
+            }
+        }
+
+        "close" => match fds.remove(&fd) {
+            Some(summary) => report.push(summary, config),
+            None => debug(format!("[close] {} => bad fd", fd), config),
+        },
+
+        "read" | "write" => {
+            // events may have been captured without the open of the fd
+            if let (false, Some(path)) = (fds.contains_key(&fd), path) {
+                fds.insert(fd, Summary::file(path));
+            }
+
+            let summary = match fds.get_mut(&fd) {
+                Some(summary) => summary,
+                None => {
+                    verbose(format!("[{}] unknown fd {}", op, fd), config);
+                    return;
+                }
+            };
+
+            let bytes = number("bytes").unwrap_or(0);
+            let size = number("size").unwrap_or(bytes);
+            let offset = number("offset");
+
+            if op == "read" {
+                summary.update_read(size, bytes, report.seq);
+                summary.update_first_io(report.time);
+                summary.update_position(offset, bytes);
+                report.update_read(summary, bytes, config);
+                report.transferred(op, bytes);
+            } else if bytes != 0 || !config.exclude_zero_byte_ops {
+                summary.update_write(size, bytes, report.seq);
+                summary.update_first_io(report.time);
+                summary.update_extent(offset, bytes);
+                summary.update_position(offset, bytes);
+                report.update_write(summary, bytes, config);
+                report.transferred(op, bytes);
+            }
+        }
+
+        op => verbose(format!("[{}] unknown op", op), config),
+    }
+}
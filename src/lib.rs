@@ -0,0 +1,45 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+extern crate atty;
+extern crate bytesize;
+#[macro_use]
+extern crate clap;
+#[macro_use]
+extern crate lazy_static;
+extern crate regex;
+
+pub mod analysis;
+pub mod cli;
+pub mod config;
+pub mod events;
+pub mod json;
+mod log;
+pub mod ltrace;
+pub mod reader;
+pub mod registry;
+pub mod report;
+pub mod runsc;
+pub mod summary;
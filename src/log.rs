@@ -26,13 +26,19 @@
 use config::Config;
 
 pub fn debug(message: String, config: &Config) {
-    if config.debug {
+    if config.debug && !config.quiet {
         eprintln!("[debug] {}", message);
     }
 }
 
 pub fn verbose(message: String, config: &Config) {
-    if config.debug || config.verbose {
+    if (config.debug || config.verbose) && !config.quiet {
         eprintln!("{}", message);
     }
 }
+
+pub fn warning(message: String, config: &Config) {
+    if !config.quiet {
+        eprintln!("[warning] {}", message);
+    }
+}
@@ -0,0 +1,140 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use analysis::{pid, strip_prefix};
+use config::Config;
+use log::*;
+use reader::{self, Lines};
+use report::Report;
+use summary::Summary;
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+// ltrace logs library calls on FILE streams, which are identified by their
+// pointer value instead of a file descriptor number
+pub fn analyze(input: &Path, report: &mut Report, config: &Config) -> io::Result<()> {
+    let file = reader::open(input, config)?;
+    let parent = report.enter(pid(input));
+
+    let mut streams: HashMap<String, Summary> = HashMap::new();
+
+    lazy_static! {
+        static ref RE_CALLER: Regex = Regex::new(r#"^\S+->"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_FOPEN: Regex =
+            Regex::new(r#"^fopen(?:64)?\("([^"]+)", .+\)\s+= (0x[0-9a-f]+)$"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_FCLOSE: Regex = Regex::new(r#"^fclose\((0x[0-9a-f]+)\)\s+= 0$"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_FREAD: Regex =
+            Regex::new(r#"^fread\(.*, (\d+), (\d+), (0x[0-9a-f]+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    lazy_static! {
+        static ref RE_FWRITE: Regex =
+            Regex::new(r#"^fwrite\(.*, (\d+), (\d+), (0x[0-9a-f]+)\)\s+= (\d+)$"#).unwrap();
+    }
+
+    for l in Lines::new(BufReader::new(file), config) {
+        let line = l?;
+        let (_pid, time, line) = strip_prefix(&line);
+        let line = &line[RE_CALLER.find(line).map_or(0, |caller| caller.end())..];
+
+        report.seq += 1;
+        report.update_time(time);
+
+        for cap in RE_FOPEN.captures_iter(line) {
+            let file = String::from(&cap[1]);
+            let stream = String::from(&cap[2]);
+
+            debug(format!("[fopen] {} => {}", stream, file), config);
+
+            if let Some(summary) = streams.insert(stream, Summary::file(file)) {
+                report.push(summary, config);
+            }
+        }
+
+        for cap in RE_FCLOSE.captures_iter(line) {
+            if let Some(summary) = streams.remove(&cap[1]) {
+                debug(
+                    format!("[fclose] {} => {}", &cap[1], summary.descriptor),
+                    config,
+                );
+                report.push(summary, config);
+            } else {
+                verbose(format!("[fclose] unknown stream {}", &cap[1]), config);
+            }
+        }
+
+        for cap in RE_FREAD.captures_iter(line) {
+            let size: u64 = cap[1].parse().unwrap();
+            let nmemb: u64 = cap[2].parse().unwrap();
+            let items: u64 = cap[4].parse().unwrap();
+
+            if let Some(summary) = streams.get_mut(&cap[3]) {
+                summary.update_read(size * nmemb, size * items, report.seq);
+                report.update_read(summary, size * items, config);
+                report.transferred("fread", size * items);
+            } else {
+                verbose(format!("[fread] unknown stream {}", &cap[3]), config);
+            }
+        }
+
+        for cap in RE_FWRITE.captures_iter(line) {
+            let size: u64 = cap[1].parse().unwrap();
+            let nmemb: u64 = cap[2].parse().unwrap();
+            let items: u64 = cap[4].parse().unwrap();
+
+            if items == 0 && config.exclude_zero_byte_ops {
+                continue;
+            }
+
+            if let Some(summary) = streams.get_mut(&cap[3]) {
+                summary.update_write(size * nmemb, size * items, report.seq);
+                report.update_write(summary, size * items, config);
+                report.transferred("fwrite", size * items);
+            } else {
+                verbose(format!("[fwrite] unknown stream {}", &cap[3]), config);
+            }
+        }
+    }
+
+    for (_, summary) in streams.drain() {
+        report.push(summary, config);
+    }
+
+    report.leave(parent);
+
+    Ok(())
+}
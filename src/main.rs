@@ -23,66 +23,65 @@
  *                                                                           *
  * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-mod analysis;
-mod config;
-mod log;
-mod summary;
+extern crate strace_analyzer;
 
-use analysis::analyze;
-use config::Config;
-use summary::Summary;
+use strace_analyzer::analysis::{analyze, standard_fds};
+use strace_analyzer::cli;
+use strace_analyzer::config::{Config, Defaults, InputFormat};
+use strace_analyzer::events;
+use strace_analyzer::ltrace;
+use strace_analyzer::report::{self, Report};
 
-extern crate atty;
-extern crate bytesize;
-#[macro_use]
-extern crate clap;
-#[macro_use]
-extern crate lazy_static;
-extern crate regex;
-
-use atty::Stream;
-use clap::{App, AppSettings, Arg};
-use std::collections::HashMap;
+use std::env;
 use std::io;
 use std::path::Path;
 
 fn main() -> io::Result<()> {
-    let color = if atty::is(Stream::Stdout) {
-        AppSettings::ColoredHelp
+    let defaults = Defaults::load()?;
+    let args: Vec<String> = env::args().collect();
+    let matches = cli::matches(&defaults, &args);
+
+    let columns = env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80);
+
+    let config = cli::config(&matches, columns);
+
+    let (report, input) = if let Some(load) = matches.value_of("load") {
+        let load = Path::new(load);
+        (Report::load(load)?, load)
     } else {
-        AppSettings::ColorNever
+        let input = Path::new(matches.value_of("file").unwrap());
+        (run(input, &config)?, input)
     };
 
-    let matches = App::new("strace-analyzer")
-        .version(crate_version!())
-        .global_setting(color)
-        .about("analyze strace output")
-        .arg(Arg::with_name("file").help("strace log").required(true))
-        .arg(Arg::with_name("debug").long("debug").help("debug output"))
-        .arg(
-            Arg::with_name("verbose")
-                .short("v")
-                .long("verbose")
-                .help("verbose output"),
-        )
-        .get_matches();
+    if let Some(save) = matches.value_of("save") {
+        report.save(Path::new(save))?;
+    }
 
-    let input = Path::new(matches.value_of("file").unwrap());
+    if let Some(other) = matches.value_of("diff") {
+        let other = Path::new(other);
+        let baseline = run(other, &config)?;
 
-    let config = Config {
-        debug: matches.is_present("debug"),
-        verbose: matches.is_present("verbose"),
-    };
+        report::diff(&report, &baseline, input, other, &config);
+    } else {
+        report.show(&config);
+    }
 
-    let stdin = Summary::file(String::from("STDIN"));
-    let stdout = Summary::file(String::from("STDOUT"));
-    let stderr = Summary::file(String::from("STDERR"));
+    Ok(())
+}
 
-    let mut fds: HashMap<u32, Summary> = HashMap::new();
+fn run(input: &Path, config: &Config) -> io::Result<Report<'static>> {
+    let mut report = Report::new();
 
-    fds.insert(0, stdin);
-    fds.insert(1, stdout);
-    fds.insert(2, stderr);
+    match config.input_format {
+        InputFormat::Strace | InputFormat::Runsc => {
+            analyze(&mut standard_fds(), input, &mut report, config)?
+        }
+        InputFormat::Ltrace => ltrace::analyze(input, &mut report, config)?,
+        InputFormat::JsonEvents => events::analyze(input, &mut report, config)?,
+    }
 
-    analyze(&mut fds, input, &config)
+    Ok(report)
 }
@@ -0,0 +1,245 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use config::Config;
+use log::*;
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Opens the log, `-` being stdin. With `--follow`, the end of the log waits
+/// for more data instead, reopening a FIFO once its writer is gone so that a
+/// restarted strace can reconnect.
+pub fn open(input: &Path, config: &Config) -> io::Result<Box<dyn Read>> {
+    if input == Path::new("-") {
+        return Ok(Box::new(io::stdin()));
+    }
+
+    let file = File::open(input)?;
+
+    if !config.follow {
+        return Ok(Box::new(file));
+    }
+
+    Ok(Box::new(Follow {
+        fifo: is_fifo(&file)?,
+        path: input.to_path_buf(),
+        file,
+    }))
+}
+
+#[cfg(unix)]
+fn is_fifo(file: &File) -> io::Result<bool> {
+    use std::os::unix::fs::FileTypeExt;
+
+    Ok(file.metadata()?.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_file: &File) -> io::Result<bool> {
+    Ok(false)
+}
+
+struct Follow {
+    path: PathBuf,
+    file: File,
+    fifo: bool,
+}
+
+impl Read for Follow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.file.read(buf)?;
+
+            if n != 0 || buf.is_empty() {
+                return Ok(n);
+            }
+
+            if self.fifo {
+                // blocks until the next writer opens the FIFO
+                self.file = File::open(&self.path)?;
+            } else {
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+}
+
+// the chunking is a building block for parsing a log in parallel, which the
+// binary doesn't do yet, hidden from the docs until it does
+
+/// Splits the log into `jobs` chunks of about the same size to be parsed in
+/// parallel, each starting at the beginning of a line.
+#[doc(hidden)]
+pub fn chunks(input: &Path, jobs: usize) -> io::Result<Vec<Range<u64>>> {
+    let len = fs::metadata(input)?.len();
+    let jobs = jobs.max(1) as u64;
+
+    let offsets: Vec<u64> = (1..jobs).map(|job| len * job / jobs).collect();
+
+    split(input, &offsets)
+}
+
+/// Splits the log at the byte `offsets`, moving each one past the end of the
+/// line it falls into, so that no line is cut in half: every line belongs to
+/// exactly one chunk.
+#[doc(hidden)]
+pub fn split(input: &Path, offsets: &[u64]) -> io::Result<Vec<Range<u64>>> {
+    let len = fs::metadata(input)?.len();
+    let mut reader = BufReader::new(File::open(input)?);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    for offset in offsets {
+        if *offset <= start {
+            continue;
+        }
+
+        // an offset right after a newline is at the start of a line already
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        let rest = reader.read_until(b'\n', &mut Vec::new())? as u64;
+        let end = (offset - 1 + rest).min(len);
+
+        if end > start {
+            chunks.push(start..end);
+            start = end;
+        }
+    }
+
+    if start < len || chunks.is_empty() {
+        chunks.push(start..len);
+    }
+
+    Ok(chunks)
+}
+
+/// Reads just the `chunk` of the log.
+#[doc(hidden)]
+pub fn chunk(input: &Path, chunk: &Range<u64>) -> io::Result<io::Take<File>> {
+    let mut file = File::open(input)?;
+    file.seek(SeekFrom::Start(chunk.start))?;
+
+    Ok(file.take(chunk.end - chunk.start))
+}
+
+pub struct Lines<'a, R> {
+    reader: R,
+    number: u64,
+    config: &'a Config,
+}
+
+impl<'a, R: BufRead> Lines<'a, R> {
+    pub fn new(reader: R, config: &'a Config) -> Lines<'a, R> {
+        Lines {
+            reader,
+            number: 0,
+            config,
+        }
+    }
+
+    /// The number of the line returned last, counting skipped ones.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    // drops the remainder of an over-long line without buffering it
+    fn skip_line(&mut self) -> io::Result<()> {
+        loop {
+            let (consumed, found) = {
+                let available = self.reader.fill_buf()?;
+                if available.is_empty() {
+                    return Ok(());
+                }
+                match available.iter().position(|b| *b == b'\n') {
+                    Some(newline) => (newline + 1, true),
+                    None => (available.len(), false),
+                }
+            };
+
+            self.reader.consume(consumed);
+
+            if found {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let max = self.config.max_line_length;
+
+        loop {
+            let mut buf = Vec::new();
+
+            let n = (&mut self.reader)
+                .take(max as u64 + 1)
+                .read_until(b'\n', &mut buf)?;
+
+            if n == 0 {
+                return Ok(None);
+            }
+
+            self.number += 1;
+
+            if buf.last() == Some(&b'\n') {
+                buf.pop();
+                if buf.last() == Some(&b'\r') {
+                    buf.pop();
+                }
+            } else if buf.len() > max {
+                warning(
+                    format!("skipping line {} longer than {} bytes", self.number, max),
+                    self.config,
+                );
+                self.skip_line()?;
+                continue;
+            }
+
+            if self.config.lenient {
+                return Ok(Some(String::from_utf8_lossy(&buf).into_owned()));
+            }
+
+            return String::from_utf8(buf)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+    }
+}
+
+impl<'a, R: BufRead> Iterator for Lines<'a, R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        match self.read_line() {
+            Ok(Some(line)) => Some(Ok(line)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use config::Config;
+use report::Report;
+use summary::Summary;
+
+use std::collections::HashMap;
+
+/// Updates the summaries of a trace from a syscall line, usually after
+/// matching it against a regex, and ignores lines it doesn't understand.
+pub type Handler =
+    Box<dyn Fn(&str, &mut HashMap<u32, Summary>, &mut Report, &Config) + Send + Sync>;
+
+/// Maps syscall names to their handlers. `Registry::default()` comes with the
+/// built-in handlers, more can be registered to analyze other syscalls or to
+/// record more about the known ones.
+///
+/// ```
+/// use strace_analyzer::registry::Registry;
+///
+/// // the kernel copies between the files without a buffer in user space
+/// let mut registry = Registry::default();
+/// registry.register("copy_file_range", |line, fds, report, _config| {
+///     let args: Vec<&str> = line["copy_file_range(".len()..].split(", ").collect();
+///     let bytes: u64 = line.rsplit("= ").next().unwrap().parse().unwrap();
+///
+///     if let Some(summary) = fds.get_mut(&args[0].parse().unwrap()) {
+///         summary.update_read(bytes, bytes, report.seq);
+///     }
+///
+///     if let Some(summary) = fds.get_mut(&args[2].parse().unwrap()) {
+///         summary.update_write(bytes, bytes, report.seq);
+///     }
+/// });
+/// assert!(registry.handles("copy_file_range"));
+/// assert!(!Registry::new().handles("read"));
+/// ```
+pub struct Registry {
+    handlers: HashMap<String, Vec<Handler>>,
+}
+
+impl Registry {
+    /// Returns a registry without any handlers.
+    pub fn new() -> Registry {
+        Registry {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers `handler` for `syscall`, after the handlers it already has.
+    pub fn register<F>(&mut self, syscall: &str, handler: F)
+    where
+        F: Fn(&str, &mut HashMap<u32, Summary>, &mut Report, &Config) + Send + Sync + 'static,
+    {
+        self.handlers
+            .entry(String::from(syscall))
+            .or_default()
+            .push(Box::new(handler));
+    }
+
+    /// Returns whether any handler is registered for `syscall`.
+    pub fn handles(&self, syscall: &str) -> bool {
+        self.handlers.contains_key(syscall)
+    }
+
+    /// Passes a line of `syscall` to its handlers, in registration order.
+    pub fn dispatch(
+        &self,
+        syscall: &str,
+        line: &str,
+        fds: &mut HashMap<u32, Summary>,
+        report: &mut Report,
+        config: &Config,
+    ) {
+        if let Some(handlers) = self.handlers.get(syscall) {
+            for handler in handlers {
+                handler(line, fds, report, config);
+            }
+        }
+    }
+}
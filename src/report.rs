@@ -0,0 +1,1454 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use config::{Config, OutputFormat, SortKey};
+use log::*;
+use summary::{
+    collapse, common_directory, humanize, render_path, show_histogram, AccessMode, FileDescription,
+    GenericFileDescriptor, SocketDescription, Summary,
+};
+
+use json::{self, Value};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// opens transferring no more than this are considered idle probes
+const STORM_IO: u64 = 4096;
+
+pub struct Process {
+    pub pid: String,
+    read_bytes: u64,
+    write_bytes: u64,
+    read_ops: u64,
+    write_ops: u64,
+    peak_fds: usize,
+    exit: Option<String>,
+}
+
+impl Process {
+    fn new(pid: String) -> Process {
+        Process {
+            pid,
+            read_bytes: 0,
+            write_bytes: 0,
+            read_ops: 0,
+            write_ops: 0,
+            peak_fds: 0,
+            exit: None,
+        }
+    }
+
+    fn update(&mut self, summary: &Summary) {
+        self.read_bytes += summary.read_bytes();
+        self.write_bytes += summary.write_bytes();
+        self.read_ops += summary.read_ops();
+        self.write_ops += summary.write_ops();
+    }
+
+    fn show(&self, config: &Config) {
+        print!(
+            "PID:{}\tread {} with {} ops\twrite {} with {} ops\tpeak {} open fds",
+            self.pid,
+            humanize(self.read_bytes, config),
+            self.read_ops,
+            humanize(self.write_bytes, config),
+            self.write_ops,
+            self.peak_fds,
+        );
+
+        match &self.exit {
+            Some(exit) => println!("\t{}", exit),
+            None => println!(),
+        }
+    }
+}
+
+// threads share the fd table of their process, but it's still interesting to
+// know which of them does the I/O
+#[derive(Default)]
+struct Thread {
+    read_bytes: u64,
+    write_bytes: u64,
+    read_ops: u64,
+    write_ops: u64,
+}
+
+pub struct Report<'a> {
+    pub seq: u64,
+    pub pid: String,
+    pub time: Option<f64>,
+    pub tid: Option<u32>,
+    start: Option<f64>,
+    end: Option<f64>,
+    timeseries: BTreeMap<u64, (u64, u64)>,
+    threads: BTreeMap<u32, Thread>,
+    processes: Vec<Process>,
+    summaries: Vec<Summary>,
+    peers: BTreeMap<u32, Summary>,
+    unknown: BTreeMap<String, u64>,
+    syscalls: BTreeMap<String, u64>,
+    syscall_bytes: BTreeMap<String, u64>,
+    skipped: u64,
+    truncated: Option<u64>,
+    evicted: u64,
+    syncs: u64,
+    // by the filesystem of the fd, if it was statfs'ed
+    syncfs: BTreeMap<Option<String>, u64>,
+    unknown_closes: u64,
+    entropy: Option<Summary>,
+    // streaming consumers get the finalized summaries instead of the report
+    sink: Option<Box<dyn FnMut(Summary) + 'a>>,
+}
+
+impl<'a> Report<'a> {
+    pub fn new() -> Report<'a> {
+        Report {
+            seq: 0,
+            pid: String::new(),
+            time: None,
+            tid: None,
+            start: None,
+            end: None,
+            timeseries: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            processes: Vec::new(),
+            summaries: Vec::new(),
+            peers: BTreeMap::new(),
+            unknown: BTreeMap::new(),
+            syscalls: BTreeMap::new(),
+            syscall_bytes: BTreeMap::new(),
+            skipped: 0,
+            truncated: None,
+            evicted: 0,
+            syncs: 0,
+            syncfs: BTreeMap::new(),
+            unknown_closes: 0,
+            entropy: None,
+            sink: None,
+        }
+    }
+
+    pub fn enter(&mut self, pid: String) -> String {
+        if !self.processes.iter().any(|process| process.pid == pid) {
+            self.processes.push(Process::new(pid.clone()));
+        }
+
+        ::std::mem::replace(&mut self.pid, pid)
+    }
+
+    pub fn leave(&mut self, pid: String) {
+        self.pid = pid;
+    }
+
+    // the process traced as `from`, e.g. by the name of its trace file, is
+    // known by its pid as soon as its lines have a prefix
+    pub fn rename_process(&mut self, from: &str, to: String) {
+        if let Some(process) = self.processes.iter_mut().find(|p| p.pid == from) {
+            process.pid = to.clone();
+        }
+
+        if self.pid == from {
+            self.pid = to;
+        }
+    }
+
+    pub fn exit(&mut self, status: String) {
+        let pid = self.pid.clone();
+
+        match self.processes.iter_mut().find(|p| p.pid == pid) {
+            Some(process) => process.exit = Some(status),
+            None => {
+                let mut process = Process::new(pid);
+                process.exit = Some(status);
+                self.processes.push(process);
+            }
+        }
+    }
+
+    pub fn update_open(&mut self, open_fds: usize) {
+        let pid = &self.pid;
+
+        if let Some(process) = self.processes.iter_mut().find(|p| &p.pid == pid) {
+            process.peak_fds = process.peak_fds.max(open_fds);
+        }
+    }
+
+    pub fn push(&mut self, summary: Summary, config: &Config) {
+        if !config.selects(&summary.descriptor) {
+            return;
+        }
+
+        let pid = &self.pid;
+
+        if let Some(process) = self.processes.iter_mut().find(|p| &p.pid == pid) {
+            process.update(&summary);
+        }
+
+        if let Some(sink) = self.sink.as_mut() {
+            sink(summary);
+            return;
+        }
+
+        self.summaries.push(summary);
+    }
+
+    /// Relabels the summaries of the already closed descriptors of a renamed
+    /// file, so the I/O of an atomic write shows up under its final name.
+    pub fn rename(&mut self, from: &str, to: &str, exchange: bool) {
+        for summary in &mut self.summaries {
+            summary.rename(from, to, exchange);
+        }
+    }
+
+    /// Takes the summary of a descriptor that is still open, evicted to
+    /// keep the fd tables within `--max-descriptors`.
+    pub fn evict(&mut self, summary: Summary, config: &Config) {
+        if self.evicted == 0 {
+            warning(
+                String::from("too many descriptors, evicting the least recently active ones"),
+                config,
+            );
+        }
+
+        self.evicted += 1;
+        self.push(summary, config);
+    }
+
+    pub fn update_time(&mut self, time: Option<f64>) {
+        if self.start.is_none() {
+            self.start = time;
+        }
+        if time > self.end {
+            self.end = time;
+        }
+        self.time = time;
+    }
+
+    fn interval(&mut self, config: &Config) -> Option<&mut (u64, u64)> {
+        match (config.timeseries, self.start, self.time) {
+            (Some(interval), Some(start), Some(time)) => {
+                let slot = ((time - start).max(0.0) / interval) as u64;
+                Some(self.timeseries.entry(slot).or_insert((0, 0)))
+            }
+            _ => None,
+        }
+    }
+
+    fn thread(&mut self, config: &Config) -> Option<&mut Thread> {
+        match (config.per_tid, self.tid) {
+            (true, Some(tid)) => Some(self.threads.entry(tid).or_default()),
+            _ => None,
+        }
+    }
+
+    /// Counts the bytes of a `getrandom` call, which are summed up in a single
+    /// summary for the whole run.
+    pub fn getrandom(&mut self, op_size: u64, bytes: u64, config: &Config) {
+        let mut summary = self.entropy.take().unwrap_or_else(Summary::entropy);
+
+        summary.update_read(op_size, bytes, self.seq);
+        summary.update_first_io(self.time);
+        self.update_read(&summary, bytes, config);
+        self.transferred("getrandom", bytes);
+
+        self.entropy = Some(summary);
+    }
+
+    /// The summary of the `getrandom` calls so far, if there were any.
+    pub fn take_entropy(&mut self) -> Option<Summary> {
+        self.entropy.take()
+    }
+
+    pub fn update_read(&mut self, summary: &Summary, bytes: u64, config: &Config) {
+        if !config.selects(&summary.descriptor) {
+            return;
+        }
+
+        if let Some(interval) = self.interval(config) {
+            interval.0 += bytes;
+        }
+
+        if let Some(thread) = self.thread(config) {
+            if bytes != 0 {
+                thread.read_bytes += bytes;
+                thread.read_ops += 1;
+            }
+        }
+    }
+
+    pub fn update_write(&mut self, summary: &Summary, bytes: u64, config: &Config) {
+        if !config.selects(&summary.descriptor) {
+            return;
+        }
+
+        if let Some(interval) = self.interval(config) {
+            interval.1 += bytes;
+        }
+
+        if let Some(thread) = self.thread(config) {
+            thread.write_bytes += bytes;
+            thread.write_ops += 1;
+        }
+    }
+
+    /// Memory moved from (`read`) or to another process, which isn't bound
+    /// to any fd.
+    pub fn update_process_vm(&mut self, pid: u32, op_size: u64, bytes: u64, read: bool) {
+        let seq = self.seq;
+        let peer = self
+            .peers
+            .entry(pid)
+            .or_insert_with(|| Summary::new(GenericFileDescriptor::ProcessMemory(pid)));
+
+        if read {
+            peer.update_read(op_size, bytes, seq);
+        } else {
+            peer.update_write(op_size, bytes, seq);
+        }
+    }
+
+    pub fn unknown_close(&mut self) {
+        self.unknown_closes += 1;
+    }
+
+    pub fn sync(&mut self) {
+        self.syncs += 1;
+    }
+
+    pub fn syncfs(&mut self, filesystem: Option<String>) {
+        *self.syncfs.entry(filesystem).or_insert(0) += 1;
+    }
+
+    pub fn skip(&mut self) {
+        self.skipped += 1;
+    }
+
+    pub fn truncate(&mut self, lines: u64) {
+        self.truncated = Some(lines);
+    }
+
+    /// Returns the number of lines analyzed if `--limit-lines` stopped the
+    /// analysis before the end of the log.
+    pub fn truncated(&self) -> Option<u64> {
+        self.truncated
+    }
+
+    pub fn unknown(&mut self, syscall: &str) {
+        *self.unknown.entry(String::from(syscall)).or_insert(0) += 1;
+    }
+
+    pub fn handled(&mut self, syscall: &str) {
+        *self.syscalls.entry(String::from(syscall)).or_insert(0) += 1;
+    }
+
+    /// Returns how often each syscall with a handler was seen.
+    pub fn syscall_stats(&self) -> &BTreeMap<String, u64> {
+        &self.syscalls
+    }
+
+    pub fn transferred(&mut self, syscall: &str, bytes: u64) {
+        *self.syscall_bytes.entry(String::from(syscall)).or_insert(0) += bytes;
+    }
+
+    /// Returns the bytes transferred per syscall, over all descriptors.
+    pub fn bytes_by_syscall(&self) -> &BTreeMap<String, u64> {
+        &self.syscall_bytes
+    }
+
+    pub fn save(&self, output: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(output)?);
+
+        for summary in self.summaries.iter().chain(self.peers.values()) {
+            writeln!(writer, "{}", summary.to_json())?;
+        }
+
+        writer.flush()
+    }
+
+    /// A report that hands every finalized summary to `sink` instead of
+    /// collecting them.
+    pub fn with_sink<F>(sink: F) -> Report<'a>
+    where
+        F: FnMut(Summary) + 'a,
+    {
+        Report {
+            sink: Some(Box::new(sink)),
+            ..Report::new()
+        }
+    }
+
+    pub fn load(input: &Path) -> io::Result<Report<'a>> {
+        let mut report = Report::new();
+
+        for (number, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+            let invalid = |message: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", input.display(), number + 1, message),
+                )
+            };
+
+            let value = json::parse(&line?).map_err(&invalid)?;
+            let summary = Summary::from_json(&value)
+                .ok_or_else(|| invalid(String::from("not a saved summary")))?;
+
+            report.summaries.push(summary);
+        }
+
+        Ok(report)
+    }
+
+    pub fn merged_by_path(&self) -> BTreeMap<String, Summary> {
+        let mut merged: BTreeMap<String, Summary> = BTreeMap::new();
+
+        for summary in &self.summaries {
+            if let GenericFileDescriptor::File(file_description) = &summary.descriptor {
+                if let Some(total) = merged.get_mut(&file_description.path) {
+                    total.merge(summary);
+                    continue;
+                }
+                merged.insert(file_description.path.clone(), summary.clone());
+            }
+        }
+
+        merged
+    }
+
+    /// The bytes read and written per access mode the files were opened
+    /// with, `None` for files whose mode is unknown.
+    pub fn by_access_mode(&self) -> BTreeMap<Option<AccessMode>, (u64, u64)> {
+        let mut modes = BTreeMap::new();
+
+        for summary in self.summaries.iter().chain(self.peers.values()) {
+            if let GenericFileDescriptor::File(file_description) = &summary.descriptor {
+                let bytes = modes.entry(file_description.mode).or_insert((0, 0));
+                bytes.0 += summary.read_bytes();
+                bytes.1 += summary.write_bytes();
+            }
+        }
+
+        modes
+    }
+
+    /// The number of reads and writes per requested op size over all
+    /// descriptors of the trace, evicted ones included.
+    pub fn global_distribution(&self) -> (BTreeMap<u64, u64>, BTreeMap<u64, u64>) {
+        let mut read = BTreeMap::new();
+        let mut write = BTreeMap::new();
+
+        for summary in self.summaries.iter().chain(self.peers.values()) {
+            for (op_size, ops) in summary.read_distribution() {
+                *read.entry(*op_size).or_insert(0) += ops;
+            }
+            for (op_size, ops) in summary.write_distribution() {
+                *write.entry(*op_size).or_insert(0) += ops;
+            }
+        }
+
+        (read, write)
+    }
+
+    /// The number of files with a tiny, small, medium and large mean op size,
+    /// the `thresholds` being the sizes at which the small, medium and large
+    /// tiers start.
+    pub fn tiers(&self, thresholds: [u64; 3]) -> [u64; 4] {
+        let mut tiers = [0; 4];
+
+        for summary in self.summaries.iter().chain(self.peers.values()) {
+            if let GenericFileDescriptor::File(_) = summary.descriptor {
+                let ops = summary.read_ops() + summary.write_ops();
+                let bytes = summary.read_bytes() + summary.write_bytes();
+
+                if let Some(mean) = bytes.checked_div(ops) {
+                    let tier = thresholds.iter().filter(|t| **t <= mean).count();
+                    tiers[tier] += 1;
+                }
+            }
+        }
+
+        tiers
+    }
+
+    fn show_tiers(&self, thresholds: [u64; 3], config: &Config) {
+        let tiers = self.tiers(thresholds);
+
+        if tiers.iter().all(|files| *files == 0) {
+            return;
+        }
+
+        let [small, medium, large] = thresholds;
+
+        println!("TIERS:");
+        for (tier, files) in [
+            format!("tiny (< {}/op)", humanize(small, config)),
+            format!("small (< {}/op)", humanize(medium, config)),
+            format!("medium (< {}/op)", humanize(large, config)),
+            format!("large (>= {}/op)", humanize(large, config)),
+        ]
+        .iter()
+        .zip(tiers.iter())
+        {
+            println!("\t{}: {} files", tier, files);
+        }
+    }
+
+    fn show_global_histogram(&self, config: &Config) {
+        let (read, write) = self.global_distribution();
+
+        if read.is_empty() && write.is_empty() {
+            return;
+        }
+
+        let mut rows = Vec::new();
+        for (op, distribution) in [("read", read), ("write", write)] {
+            for (op_size, ops) in distribution {
+                rows.push((
+                    format!("{} {}: {} ops", op, humanize(op_size, config), ops),
+                    ops,
+                ));
+            }
+        }
+
+        println!("OP SIZES:");
+        show_histogram(&rows, 1, config);
+    }
+
+    fn show_access_modes(&self, config: &Config) {
+        let mut modes: Vec<(Option<AccessMode>, (u64, u64))> = self
+            .by_access_mode()
+            .into_iter()
+            .filter(|(_, (read, write))| *read != 0 || *write != 0)
+            .collect();
+
+        if modes.is_empty() {
+            return;
+        }
+
+        // the standard streams and other inherited files last
+        modes.sort_by_key(|(mode, _)| mode.is_none());
+
+        println!("ACCESS MODES:");
+        for (mode, (read, write)) in modes {
+            let mut bytes = Vec::new();
+            if read != 0 {
+                bytes.push(format!("read {}", humanize(read, config)));
+            }
+            if write != 0 {
+                bytes.push(format!("write {}", humanize(write, config)));
+            }
+
+            println!(
+                "\t{}: {}",
+                mode.map_or("unknown", AccessMode::name),
+                bytes.join(", ")
+            );
+        }
+    }
+
+    /// Merges all sockets connected to the same remote endpoint, e.g. the
+    /// connections of a pool, by their address.
+    pub fn merged_by_endpoint(&self) -> BTreeMap<String, Summary> {
+        let mut merged: BTreeMap<String, Summary> = BTreeMap::new();
+
+        for summary in &self.summaries {
+            if let Some(endpoint) = endpoint(summary) {
+                if let Some(total) = merged.get_mut(&endpoint) {
+                    total.merge(summary);
+                    continue;
+                }
+                merged.insert(endpoint, summary.clone());
+            }
+        }
+
+        merged
+    }
+
+    /// Summaries and peers with the rotated files matching `--collapse-pattern`
+    /// and, with `--merge-sockets-by-endpoint`, the sockets connected to the
+    /// same address merged into one logical entry each, in order of their
+    /// first appearance.
+    fn collapsed(&self, config: &Config) -> Vec<Summary> {
+        let mut collapsed: Vec<Summary> = Vec::new();
+        let mut logical: BTreeMap<String, usize> = BTreeMap::new();
+
+        for summary in self.summaries.iter().chain(self.peers.values()) {
+            let descriptor = match (&summary.descriptor, &config.collapse) {
+                (GenericFileDescriptor::File(file_description), Some(pattern)) => {
+                    collapse(&file_description.path, pattern)
+                        .map(|path| GenericFileDescriptor::File(FileDescription::new(path)))
+                }
+                (GenericFileDescriptor::Socket(_), _) if config.merge_sockets => endpoint(summary)
+                    .map(|endpoint| {
+                        let mut socket_description = SocketDescription::new();
+                        socket_description.update_connect(endpoint);
+                        GenericFileDescriptor::Socket(socket_description)
+                    }),
+                _ => None,
+            };
+
+            match descriptor {
+                Some(descriptor) => {
+                    let key = descriptor.to_string();
+
+                    if let Some(&index) = logical.get(&key) {
+                        collapsed[index].merge(summary);
+                        continue;
+                    }
+
+                    let mut summary = summary.clone();
+                    summary.descriptor = descriptor;
+                    logical.insert(key, collapsed.len());
+                    collapsed.push(summary);
+                }
+                None => collapsed.push(summary.clone()),
+            }
+        }
+
+        collapsed
+    }
+
+    /// Returns the `n` files and sockets with the most failed syscalls, the
+    /// failures of all descriptors of the same path combined.
+    pub fn top_errors(&self, n: usize) -> Vec<(String, Summary)> {
+        let mut merged: BTreeMap<String, Summary> = BTreeMap::new();
+
+        for summary in self
+            .summaries
+            .iter()
+            .filter(|summary| summary.failures() != 0)
+        {
+            let key = match &summary.descriptor {
+                GenericFileDescriptor::File(file_description) => file_description.path.clone(),
+                descriptor => descriptor.to_string(),
+            };
+
+            if let Some(total) = merged.get_mut(&key) {
+                total.merge(summary);
+                continue;
+            }
+            merged.insert(key, summary.clone());
+        }
+
+        let mut ranked: Vec<(String, Summary)> = merged.into_iter().collect();
+        ranked.sort_by_key(|(_, summary)| Reverse(summary.failures()));
+        ranked.truncate(n);
+        ranked
+    }
+
+    fn show_top_errors(&self, n: usize, config: &Config) {
+        let ranked = self.top_errors(n);
+
+        if ranked.is_empty() {
+            return;
+        }
+
+        println!("TOP ERRORS:");
+
+        for (key, summary) in ranked {
+            let errors: Vec<String> = summary
+                .errors()
+                .iter()
+                .map(|(errno, count)| format!("{} {}", errno, count))
+                .collect();
+
+            let key = match &summary.descriptor {
+                GenericFileDescriptor::File(_) => render_path(&key, config),
+                _ => summary.label(config).replace("\n\t", " "),
+            };
+
+            println!(
+                "\t{}: {} failed ({})",
+                key,
+                summary.failures(),
+                errors.join(", ")
+            );
+        }
+    }
+
+    fn show_hot_files(&self, config: &Config) {
+        let merged = self.merged_by_path();
+
+        let render = |path: &str| render_path(path, config);
+
+        let most_read = merged
+            .iter()
+            .filter(|(_, summary)| summary.read_bytes() != 0)
+            .max_by_key(|(_, summary)| summary.read_bytes());
+
+        let most_written = merged
+            .iter()
+            .filter(|(_, summary)| summary.write_bytes() != 0)
+            .max_by_key(|(_, summary)| summary.write_bytes());
+
+        if most_read.is_none() && most_written.is_none() {
+            return;
+        }
+
+        println!("HOT FILES:");
+
+        if let Some((path, summary)) = most_read {
+            println!(
+                "\tmost read: {} ({})",
+                render(path),
+                humanize(summary.read_bytes(), config)
+            );
+        }
+
+        if let Some((path, summary)) = most_written {
+            println!(
+                "\tmost written: {} ({})",
+                render(path),
+                humanize(summary.write_bytes(), config)
+            );
+        }
+    }
+
+    // the read:write byte ratio of every file, all its descriptors combined
+    fn show_ratios(&self, config: &Config) {
+        let ratios: Vec<(String, Summary)> = self
+            .merged_by_path()
+            .into_iter()
+            .filter(|(_, summary)| summary.read_bytes() != 0 || summary.write_bytes() != 0)
+            .filter(|(_, summary)| summary.visible(config))
+            .collect();
+
+        if ratios.is_empty() {
+            return;
+        }
+
+        println!("READ:WRITE RATIOS:");
+        for (path, summary) in ratios {
+            println!("\t{}: {}", render_path(&path, config), summary.ratio());
+        }
+    }
+
+    // directories whose files were opened many times with (next to) no I/O,
+    // typically a discovery loop probing for files that are never used
+    fn show_storms(&self, threshold: u64, config: &Config) {
+        let mut opens: BTreeMap<&str, u64> = BTreeMap::new();
+
+        for summary in &self.summaries {
+            if let GenericFileDescriptor::File(file_description) = &summary.descriptor {
+                let path = file_description.path.as_str();
+
+                let directory = match path.rfind('/') {
+                    Some(0) => "/",
+                    Some(slash) => &path[..slash],
+                    None => continue,
+                };
+
+                if summary.read_bytes() + summary.write_bytes() <= STORM_IO {
+                    *opens.entry(directory).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut storms: Vec<(&str, u64)> = opens
+            .into_iter()
+            .filter(|(_, count)| *count >= threshold)
+            .collect();
+
+        if storms.is_empty() {
+            return;
+        }
+
+        storms.sort_by_key(|(_, count)| Reverse(*count));
+
+        println!("OPEN STORMS:");
+        for (directory, count) in storms {
+            println!(
+                "\t{}: {} opens with at most {} of I/O each",
+                render_path(directory, config),
+                count,
+                humanize(STORM_IO, config)
+            );
+        }
+    }
+
+    // one record per shown summary with just the requested fields
+    fn show_records(&self, config: &Config) {
+        let fields: Vec<&str> = config.fields.iter().map(String::as_str).collect();
+
+        if config.format == OutputFormat::Csv {
+            println!("{}", fields.join(","));
+        }
+
+        let collapsed;
+        let mut summaries: Vec<&Summary> = if config.collapse.is_some() || config.merge_sockets {
+            collapsed = self.collapsed(config);
+            collapsed.iter().collect()
+        } else {
+            self.summaries.iter().chain(self.peers.values()).collect()
+        };
+
+        if config.sort != SortKey::Closed {
+            summaries.sort_by_key(|summary| Reverse(summary.weight(config.sort)));
+        }
+
+        let summaries = summaries
+            .into_iter()
+            .filter(|summary| summary.visible(config));
+
+        if config.format == OutputFormat::Prometheus {
+            show_metrics(summaries, config);
+            return;
+        }
+
+        if config.format == OutputFormat::Table {
+            let summaries: Vec<&Summary> = summaries.collect();
+
+            let relative;
+            let summaries = match relative_paths(&summaries, config) {
+                Some((base, relative_summaries)) => {
+                    println!("paths relative to {}", render_path(&base, config));
+                    relative = relative_summaries;
+                    relative.iter().collect()
+                }
+                None => summaries,
+            };
+
+            if config.with_index {
+                for line in index(&summaries, config) {
+                    println!("{}", line);
+                }
+            }
+
+            let rows: Vec<Vec<Value>> = summaries
+                .iter()
+                .map(|summary| {
+                    fields
+                        .iter()
+                        .map(|field| summary.field(field, config))
+                        .collect()
+                })
+                .collect();
+
+            for line in table(&fields, &rows) {
+                println!("{}", line);
+            }
+            return;
+        }
+
+        for summary in summaries {
+            let values = fields.iter().map(|field| summary.field(field, config));
+
+            match config.format {
+                OutputFormat::Csv => {
+                    let values: Vec<String> = values.map(|value| csv(&value)).collect();
+                    println!("{}", values.join(","));
+                }
+                _ => {
+                    let object = fields.iter().map(|field| String::from(*field)).zip(values);
+                    println!("{}", Value::Object(object.collect()));
+                }
+            }
+        }
+    }
+
+    fn show_timeseries(&self, interval: f64) {
+        println!("time,read_bytes,write_bytes");
+
+        if let Some(last) = self.timeseries.keys().next_back() {
+            for slot in 0..=*last {
+                let (read, write) = self.timeseries.get(&slot).unwrap_or(&(0, 0));
+                println!("{},{},{}", slot as f64 * interval, read, write);
+            }
+        }
+    }
+
+    pub fn show(&self, config: &Config) {
+        if let Some(interval) = config.timeseries {
+            self.show_timeseries(interval);
+            return;
+        }
+
+        if config.format != OutputFormat::Text {
+            self.show_records(config);
+            return;
+        }
+
+        if config.hot_files {
+            self.show_hot_files(config);
+        }
+
+        if let Some(n) = config.top_errors {
+            self.show_top_errors(n, config);
+        }
+
+        if config.pid_overview {
+            for process in &self.processes {
+                process.show(config);
+            }
+        }
+
+        for (tid, thread) in &self.threads {
+            println!(
+                "TID:{}\tread {} with {} ops\twrite {} with {} ops",
+                tid,
+                humanize(thread.read_bytes, config),
+                thread.read_ops,
+                humanize(thread.write_bytes, config),
+                thread.write_ops,
+            );
+        }
+
+        let collapsed;
+        let mut summaries: Vec<&Summary> = if config.collapse.is_some() || config.merge_sockets {
+            collapsed = self.collapsed(config);
+            collapsed.iter().collect()
+        } else {
+            self.summaries.iter().chain(self.peers.values()).collect()
+        };
+
+        if config.sort != SortKey::Closed {
+            summaries.sort_by_key(|summary| Reverse(summary.weight(config.sort)));
+        }
+
+        let totals = summaries.iter().fold((0, 0), |(read, write), summary| {
+            (read + summary.read_bytes(), write + summary.write_bytes())
+        });
+
+        let visible: Vec<&Summary> = summaries
+            .into_iter()
+            .filter(|summary| summary.visible(config))
+            .collect();
+
+        let relative;
+        let visible = match relative_paths(&visible, config) {
+            Some((base, summaries)) => {
+                println!("paths relative to {}", render_path(&base, config));
+                relative = summaries;
+                relative.iter().collect()
+            }
+            None => visible,
+        };
+
+        if config.with_index {
+            for line in index(&visible, config) {
+                println!("{}", line);
+            }
+        }
+
+        for summary in visible {
+            summary.show(config, totals);
+        }
+
+        if config.ratio {
+            self.show_ratios(config);
+        }
+
+        for summary in &self.summaries {
+            match summary.retry_rate() {
+                Some(rate) if rate * 100.0 > config.retry_threshold => warning(
+                    format!(
+                        "{}: {:.0}% of the I/O attempts failed with EINTR/EAGAIN, busy-looping?",
+                        summary.label(config).replace("\n\t", " "),
+                        rate * 100.0,
+                    ),
+                    config,
+                ),
+                _ => {}
+            }
+        }
+
+        if config.unknown_syscalls && !self.unknown.is_empty() {
+            let mut unknown: Vec<(&String, &u64)> = self.unknown.iter().collect();
+            unknown.sort_by(|a, b| b.1.cmp(a.1));
+
+            println!("UNKNOWN SYSCALLS:");
+            for (syscall, count) in unknown {
+                println!("\t{}: {}", syscall, count);
+            }
+        }
+
+        if config.syscall_stats && !self.syscalls.is_empty() {
+            let mut syscalls: Vec<(&String, &u64)> = self.syscalls.iter().collect();
+            syscalls.sort_by(|a, b| b.1.cmp(a.1));
+
+            println!("SYSCALLS:");
+            for (syscall, count) in syscalls {
+                println!("\t{}: {}", syscall, count);
+            }
+        }
+
+        if config.summary_by_syscall && !self.syscall_bytes.is_empty() {
+            let mut syscalls: Vec<(&String, &u64)> = self.syscall_bytes.iter().collect();
+            syscalls.sort_by(|a, b| b.1.cmp(a.1));
+
+            println!("BYTES BY SYSCALL:");
+            for (syscall, bytes) in syscalls {
+                println!("\t{}: {}", syscall, humanize(*bytes, config));
+            }
+        }
+
+        if config.by_access_mode {
+            self.show_access_modes(config);
+        }
+
+        if config.global_histogram {
+            self.show_global_histogram(config);
+        }
+
+        if let Some(thresholds) = config.tiers {
+            self.show_tiers(thresholds, config);
+        }
+
+        if let Some(threshold) = config.storms {
+            self.show_storms(threshold, config);
+        }
+
+        let peak_fds = self.processes.iter().map(|p| p.peak_fds).max();
+        if let Some(peak_fds) = peak_fds.filter(|peak_fds| *peak_fds != 0) {
+            println!("peak open descriptors: {}", peak_fds);
+        }
+
+        if !config.pid_overview {
+            for (i, process) in self.processes.iter().enumerate() {
+                match &process.exit {
+                    Some(exit) if i == 0 => println!("program {}", exit),
+                    Some(exit) => println!("PID:{} {}", process.pid, exit),
+                    None => {}
+                }
+            }
+        }
+
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            println!("trace span: {:.1}s", end - start);
+        }
+
+        if self.evicted != 0 {
+            println!(
+                "evicted {} descriptors before their close, later I/O on them is not counted",
+                self.evicted
+            );
+        }
+
+        if config.sync && self.syncs != 0 {
+            println!("sync: {} global sync() calls", self.syncs);
+        }
+
+        if config.sync {
+            for (filesystem, calls) in &self.syncfs {
+                match filesystem {
+                    Some(filesystem) => println!("syncfs: {} calls on {}", calls, filesystem),
+                    None => println!("syncfs: {} calls on filesystems never statfs'ed", calls),
+                }
+            }
+        }
+
+        if config.verbose && self.unknown_closes != 0 {
+            println!(
+                "closed {} fds that were never opened, the trace may have started mid-stream",
+                self.unknown_closes
+            );
+        }
+
+        if self.skipped != 0 {
+            println!(
+                "skipped {} lines not matching the strace grammar",
+                self.skipped
+            );
+        }
+
+        if let Some(lines) = self.truncated {
+            println!("sample: stopped after the first {} lines", lines);
+        }
+
+        if let Some(k) = config.sample {
+            println!(
+                "sample: analyzed 1 in {} lines, the figures are approximate",
+                k
+            );
+        }
+    }
+}
+
+impl<'a> Default for Report<'a> {
+    fn default() -> Report<'a> {
+        Report::new()
+    }
+}
+
+// with --relative-paths, copies of the summaries with the base directory
+// stripped from the paths of the files below it, which is the directory all
+// shown files have in common unless given
+fn relative_paths(summaries: &[&Summary], config: &Config) -> Option<(String, Vec<Summary>)> {
+    if !config.relative_paths {
+        return None;
+    }
+
+    let paths = summaries
+        .iter()
+        .filter_map(|summary| match &summary.descriptor {
+            GenericFileDescriptor::File(file_description) => Some(file_description.path.as_str()),
+            _ => None,
+        });
+
+    let base = match &config.relative_base {
+        Some(base) => String::from(base.trim_end_matches('/')),
+        None => common_directory(paths)?,
+    };
+
+    let prefix = format!("{}/", base);
+    let relative = summaries
+        .iter()
+        .map(|summary| {
+            let mut summary = (*summary).clone();
+            if let GenericFileDescriptor::File(file_description) = &mut summary.descriptor {
+                if let Some(path) = file_description.path.strip_prefix(prefix.as_str()) {
+                    file_description.path = String::from(path);
+                }
+            }
+            summary
+        })
+        .collect();
+
+    Some((base, relative))
+}
+
+// the remote address of a connected socket
+fn endpoint(summary: &Summary) -> Option<String> {
+    match &summary.descriptor {
+        GenericFileDescriptor::Socket(socket_description)
+            if !socket_description.connect().is_empty() =>
+        {
+            Some(String::from(socket_description.connect()))
+        }
+        _ => None,
+    }
+}
+
+const METRICS: &[(&str, &str)] = &[
+    ("strace_read_bytes_total", "Bytes read per path."),
+    ("strace_read_ops_total", "Read syscalls per path."),
+    ("strace_write_bytes_total", "Bytes written per path."),
+    ("strace_write_ops_total", "Write syscalls per path."),
+];
+
+// one series per kind and path, the paths beyond --max-labels folded into
+// one "(other)" series per kind to keep the cardinality bounded
+fn show_metrics<'a, I>(summaries: I, config: &Config)
+where
+    I: Iterator<Item = &'a Summary>,
+{
+    let mut series: BTreeMap<(String, String), [u64; 4]> = BTreeMap::new();
+
+    for summary in summaries {
+        let path = match summary.field("path", config) {
+            Value::String(path) => path,
+            _ => String::new(),
+        };
+
+        let values = series
+            .entry((String::from(summary.descriptor.name()), path))
+            .or_insert([0; 4]);
+
+        values[0] += summary.read_bytes();
+        values[1] += summary.read_ops();
+        values[2] += summary.write_bytes();
+        values[3] += summary.write_ops();
+    }
+
+    let mut series: Vec<((String, String), [u64; 4])> = series.into_iter().collect();
+
+    if let Some(max) = config.max_labels {
+        if series.len() > max {
+            series.sort_by_key(|(_, values)| Reverse(values[0] + values[2]));
+
+            let mut other: BTreeMap<(String, String), [u64; 4]> = BTreeMap::new();
+
+            for ((kind, _), values) in series.split_off(max) {
+                let total = other
+                    .entry((kind, String::from("(other)")))
+                    .or_insert([0; 4]);
+
+                for (total, value) in total.iter_mut().zip(values.iter()) {
+                    *total += value;
+                }
+            }
+
+            series.extend(other);
+            series.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+    }
+
+    for (i, (name, help)) in METRICS.iter().enumerate() {
+        println!("# HELP {} {}", name, help);
+        println!("# TYPE {} counter", name);
+
+        for ((kind, path), values) in &series {
+            println!(
+                "{}",
+                metric(name, &[("kind", kind), ("path", path)], values[i])
+            );
+        }
+    }
+}
+
+/// Renders a sample in the Prometheus text format, escaping the label values.
+///
+/// ```
+/// use strace_analyzer::report::metric;
+///
+/// assert_eq!(
+///     metric("strace_read_bytes_total", &[("path", "/srv/data")], 12345),
+///     "strace_read_bytes_total{path=\"/srv/data\"} 12345"
+/// );
+/// assert_eq!(
+///     metric("strace_read_bytes_total", &[("path", "a\"b\\c\nd")], 0),
+///     r#"strace_read_bytes_total{path="a\"b\\c\nd"} 0"#
+/// );
+/// ```
+pub fn metric(name: &str, labels: &[(&str, &str)], value: u64) -> String {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(label, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+
+            format!("{}=\"{}\"", label, value)
+        })
+        .collect();
+
+    format!("{}{{{}}} {}", name, labels.join(","), value)
+}
+
+/// A table of contents for the `summaries` about to be shown: how many
+/// there are and their bytes, in total and per kind of descriptor.
+///
+/// ```
+/// use strace_analyzer::config::Config;
+/// use strace_analyzer::report::index;
+/// use strace_analyzer::summary::Summary;
+///
+/// let mut a = Summary::file(String::from("/srv/a"));
+/// a.update_read(4096, 4096, 1);
+/// let mut b = Summary::file(String::from("/srv/b"));
+/// b.update_write(1024, 1024, 2);
+/// let mut socket = Summary::socket();
+/// socket.update_write(100, 100, 3);
+///
+/// assert_eq!(
+///     index(&[&a, &b, &socket], &Config::default()),
+///     [
+///         "INDEX: 3 rows, read 4.0K, write 1.1K",
+///         "\tfile: 2 rows, read 4.0K, write 1.0K",
+///         "\tsocket: 1 rows, read 0B, write 100B",
+///     ]
+/// );
+/// ```
+pub fn index(summaries: &[&Summary], config: &Config) -> Vec<String> {
+    let mut kinds: BTreeMap<&str, (usize, u64, u64)> = BTreeMap::new();
+
+    for summary in summaries {
+        let kind = kinds.entry(summary.descriptor.name()).or_insert((0, 0, 0));
+        kind.0 += 1;
+        kind.1 += summary.read_bytes();
+        kind.2 += summary.write_bytes();
+    }
+
+    let line = |(rows, read, write): (usize, u64, u64)| {
+        format!(
+            "{} rows, read {}, write {}",
+            rows,
+            humanize(read, config),
+            humanize(write, config)
+        )
+    };
+
+    let total = kinds.values().fold((0, 0, 0), |total, kind| {
+        (total.0 + kind.0, total.1 + kind.1, total.2 + kind.2)
+    });
+
+    let mut lines = vec![format!("INDEX: {}", line(total))];
+    for (kind, counts) in kinds {
+        lines.push(format!("\t{}: {}", kind, line(counts)));
+    }
+
+    lines
+}
+
+/// Lays out `rows` under `header` in padded columns, numbers right-justified
+/// and everything else left-justified.
+///
+/// ```
+/// use strace_analyzer::json::Value;
+/// use strace_analyzer::report::table;
+///
+/// let row = |path: &str, bytes: u64| {
+///     vec![Value::String(String::from(path)), Value::Number(bytes.to_string())]
+/// };
+///
+/// let lines = table(
+///     &["path", "read_bytes"],
+///     &[row("/srv/a", 4096), row("/srv/longer/path", 12)],
+/// );
+///
+/// assert_eq!(
+///     lines,
+///     [
+///         "path              read_bytes",
+///         "/srv/a                  4096",
+///         "/srv/longer/path          12",
+///     ]
+/// );
+/// ```
+pub fn table(header: &[&str], rows: &[Vec<Value>]) -> Vec<String> {
+    let cells: Vec<Vec<(String, bool)>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    Value::Number(n) => (n.clone(), true),
+                    Value::Null => (String::from("-"), true),
+                    Value::String(s) => (s.clone(), false),
+                    value => (value.to_string(), false),
+                })
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|(cell, _)| cell.chars().count())
+                .fold(name.len(), usize::max)
+        })
+        .collect();
+
+    // the header is aligned like the column below it
+    let right: Vec<bool> = (0..header.len())
+        .map(|i| cells.first().and_then(|row| row.get(i)).map(|cell| cell.1) == Some(true))
+        .collect();
+
+    let header: Vec<(String, bool)> = header
+        .iter()
+        .zip(right)
+        .map(|(name, right)| (String::from(*name), right))
+        .collect();
+
+    let mut lines = Vec::new();
+
+    for row in std::iter::once(&header).chain(cells.iter()) {
+        let padded: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|((cell, right), width)| {
+                if *right {
+                    format!("{:>1$}", cell, width)
+                } else {
+                    format!("{:<1$}", cell, width)
+                }
+            })
+            .collect();
+
+        lines.push(padded.join("  ").trim_end().to_string());
+    }
+
+    lines
+}
+
+fn csv(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) if s.contains(',') || s.contains('"') => {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        }
+        Value::String(s) | Value::Number(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn signed(value: i64) -> String {
+    if value < 0 {
+        format!("-{}", value.abs())
+    } else {
+        format!("+{}", value)
+    }
+}
+
+fn signed_bytes(value: i64, config: &Config) -> String {
+    if value < 0 {
+        format!("-{}", humanize(value.unsigned_abs(), config))
+    } else {
+        format!("+{}", humanize(value as u64, config))
+    }
+}
+
+pub fn diff(report: &Report, baseline: &Report, input: &Path, other: &Path, config: &Config) {
+    let current = report.merged_by_path();
+    let previous = baseline.merged_by_path();
+
+    let render = |path: &str| render_path(path, config);
+
+    for (path, summary) in &current {
+        if let Some(old) = previous.get(path) {
+            let read_bytes = summary.read_bytes() as i64 - old.read_bytes() as i64;
+            let read_ops = summary.read_ops() as i64 - old.read_ops() as i64;
+            let write_bytes = summary.write_bytes() as i64 - old.write_bytes() as i64;
+            let write_ops = summary.write_ops() as i64 - old.write_ops() as i64;
+
+            if read_bytes == 0 && read_ops == 0 && write_bytes == 0 && write_ops == 0 {
+                continue;
+            }
+
+            println!("FILE:{}", render(path));
+
+            if read_bytes != 0 || read_ops != 0 {
+                println!(
+                    "\tread {} with {} ops",
+                    signed_bytes(read_bytes, config),
+                    signed(read_ops)
+                );
+            }
+
+            if write_bytes != 0 || write_ops != 0 {
+                println!(
+                    "\twrite {} with {} ops",
+                    signed_bytes(write_bytes, config),
+                    signed(write_ops)
+                );
+            }
+        } else {
+            println!("FILE:{}\n\tonly in {}", render(path), input.display());
+        }
+    }
+
+    for path in previous.keys() {
+        if !current.contains_key(path) {
+            println!("FILE:{}\n\tonly in {}", render(path), other.display());
+        }
+    }
+}
@@ -0,0 +1,65 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  Copyright  (C)  2015-2018  Christian Krause                              *
+ *                                                                           *
+ *  Christian Krause  <christian.krause@mailbox.org>                         *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *
+ *                                                                           *
+ *  This file is part of strace-analyzer.                                    *
+ *                                                                           *
+ *  strace-analyzer is free software: you can redistribute it and/or modify  *
+ *  it under the terms of the GNU General Public License as published by     *
+ *  the Free Software Foundation, either version 3 of the license, or any    *
+ *  later version.                                                           *
+ *                                                                           *
+ *  strace-analyzer is distributed in the hope that it will be useful, but   *
+ *  WITHOUT ANY WARRANTY; without even the implied warranty of               *
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU         *
+ *  General Public License for more details.                                 *
+ *                                                                           *
+ *  You should have received a copy of the GNU General Public License along  *
+ *  with strace-analyzer. If not, see <http://www.gnu.org/licenses/>.        *
+ *                                                                           *
+ * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use regex::Regex;
+
+/// Turns a line of a gVisor (runsc) strace debug log into a strace line.
+///
+/// runsc decorates every line with a glog header, i.e. the level tag, the
+/// time of day, the goroutine id and the source location, followed by the
+/// thread group and thread id, the command and whether the syscall was
+/// entered or exited. Only exits carry the result, so entries and lines that
+/// are not from the strace emitter at all give `None`. The thread id and the
+/// time are kept as the prefix strace would print with `-f -tt`.
+///
+/// ```
+/// use strace_analyzer::runsc::normalize;
+///
+/// let exit = "D0612 12:34:56.789012   42 strace.go:596] [   7:   9] cat X \
+///             read(3, \"\", 4096) = 4096";
+/// assert_eq!(
+///     normalize(exit).unwrap(),
+///     "9 12:34:56.789012 read(3, \"\", 4096) = 4096"
+/// );
+///
+/// let entry = "D0612 12:34:56.789000   42 strace.go:561] [   7:   9] cat E \
+///              read(3, 0x0, 4096)";
+/// assert_eq!(normalize(entry), None);
+/// ```
+pub fn normalize(line: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE_RUNSC: Regex = Regex::new(
+            r#"^[DIWEF]\d{4} (\d\d:\d\d:\d\d\.\d+)\s+\d+ strace\.go:\d+\] \[\s*\d+:\s*(\d+)\] \S+ ([EX]) (.*)$"#
+        ).unwrap();
+    }
+
+    let cap = RE_RUNSC.captures(line)?;
+
+    if &cap[3] == "E" {
+        return None;
+    }
+
+    Some(format!("{} {} {}", &cap[2], &cap[1], &cap[4]))
+}
@@ -23,22 +23,117 @@
  *                                                                           *
  * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use config::Config;
+use config::{Config, Kind, SortKey, Unit};
+use json::{self, Value};
 use log::*;
 
 use bytesize::ByteSize;
-use std::collections::HashMap;
+use regex::{Captures, Regex};
+use std::collections::{BTreeMap, HashMap};
 
 use std::fmt;
 
+/// The fields available for `--fields`.
+pub const FIELDS: &[&str] = &[
+    "kind",
+    "path",
+    "read_bytes",
+    "read_ops",
+    "write_bytes",
+    "write_ops",
+    "max_read_op",
+    "max_write_op",
+    "eof",
+    "accepted",
+    "sequential_ops",
+    "random_ops",
+    "first_op",
+    "last_op",
+];
+
+/// How a file was opened, from the access mode of its open flags. Files
+/// opened with `O_APPEND` for writing count as appending regardless.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccessMode {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    Append,
+}
+
+impl AccessMode {
+    /// The access mode of the flags of an `open` family syscall.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::AccessMode;
+    ///
+    /// assert_eq!(AccessMode::from_flags("O_RDONLY|O_CLOEXEC"), Some(AccessMode::ReadOnly));
+    /// assert_eq!(AccessMode::from_flags("O_WRONLY|O_CREAT|O_APPEND"), Some(AccessMode::Append));
+    /// assert_eq!(AccessMode::from_flags("0644"), None);
+    /// ```
+    pub fn from_flags(flags: &str) -> Option<AccessMode> {
+        let flags: Vec<&str> = flags
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .collect();
+
+        let mode = if flags.contains(&"O_RDWR") {
+            AccessMode::ReadWrite
+        } else if flags.contains(&"O_WRONLY") {
+            AccessMode::WriteOnly
+        } else if flags.contains(&"O_RDONLY") {
+            return Some(AccessMode::ReadOnly);
+        } else {
+            return None;
+        };
+
+        if flags.contains(&"O_APPEND") {
+            Some(AccessMode::Append)
+        } else {
+            Some(mode)
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AccessMode::ReadOnly => "read-only",
+            AccessMode::WriteOnly => "write-only",
+            AccessMode::ReadWrite => "read-write",
+            AccessMode::Append => "append",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<AccessMode> {
+        match name {
+            "read-only" => Some(AccessMode::ReadOnly),
+            "write-only" => Some(AccessMode::WriteOnly),
+            "read-write" => Some(AccessMode::ReadWrite),
+            "append" => Some(AccessMode::Append),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FileDescription {
     pub path: String,
+    /// as reported by the stat family, if the file was stat'ed through its fd
+    pub size: Option<u64>,
+    /// unknown for files that weren't opened in the trace, e.g. STDIN
+    pub mode: Option<AccessMode>,
+    /// the filesystem type and block size, if the file was statfs'ed
+    pub filesystem: Option<String>,
+    pub block_size: Option<u64>,
 }
 
 impl FileDescription {
     pub fn new(path: String) -> FileDescription {
-        FileDescription { path }
+        FileDescription {
+            path,
+            size: None,
+            mode: None,
+            filesystem: None,
+            block_size: None,
+        }
     }
 }
 
@@ -62,6 +157,70 @@ impl SocketDescription {
         }
     }
 
+    pub fn bind(&self) -> &str {
+        &self.bind
+    }
+
+    /// The address the socket connected to, empty if unknown. A nonblocking
+    /// connect that is still in progress counts as connected.
+    pub fn connect(&self) -> &str {
+        &self.connect
+    }
+
+    /// The remote address, or the bound one if the socket never connected,
+    /// as `host:port` or the path of a unix socket. Families without either
+    /// are given as strace printed them.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::SocketDescription;
+    ///
+    /// let mut socket_description = SocketDescription::new();
+    /// assert_eq!(socket_description.address(), None);
+    ///
+    /// let unix = "{sa_family=AF_UNIX, sun_path=\"/run/app.sock\"}";
+    /// socket_description.update_bind(String::from(unix));
+    /// assert_eq!(socket_description.address().unwrap(), "/run/app.sock");
+    ///
+    /// socket_description.update_connect(String::from(
+    ///     "{sa_family=AF_INET6, sin6_port=htons(443), sin6_flowinfo=htonl(0), \
+    ///      inet_pton(AF_INET6, \"::1\", &sin6_addr), sin6_scope_id=0}",
+    /// ));
+    /// assert_eq!(socket_description.address().unwrap(), "[::1]:443");
+    /// ```
+    pub fn address(&self) -> Option<String> {
+        lazy_static! {
+            static ref RE_UNIX: Regex = Regex::new(r#"sun_path=@?"([^"]*)""#).unwrap();
+        }
+
+        lazy_static! {
+            static ref RE_INET: Regex =
+                Regex::new(r#"sin_port=htons\((\d+)\), sin_addr=inet_addr\("([^"]+)"\)"#).unwrap();
+        }
+
+        lazy_static! {
+            static ref RE_INET6: Regex =
+                Regex::new(r#"sin6_port=htons\((\d+)\).*inet_pton\(AF_INET6, "([^"]+)""#).unwrap();
+        }
+
+        let address = if self.connect.is_empty() {
+            &self.bind
+        } else {
+            &self.connect
+        };
+
+        if address.is_empty() {
+            None
+        } else if let Some(cap) = RE_UNIX.captures(address) {
+            Some(String::from(&cap[1]))
+        } else if let Some(cap) = RE_INET.captures(address) {
+            Some(format!("{}:{}", &cap[2], &cap[1]))
+        } else if let Some(cap) = RE_INET6.captures(address) {
+            Some(format!("[{}]:{}", &cap[2], &cap[1]))
+        } else {
+            Some(address.clone())
+        }
+    }
+
     pub fn update_bind(&mut self, address: String) {
         self.bind = address;
     }
@@ -71,6 +230,12 @@ impl SocketDescription {
     }
 }
 
+impl Default for SocketDescription {
+    fn default() -> SocketDescription {
+        SocketDescription::new()
+    }
+}
+
 impl fmt::Display for SocketDescription {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "SOCKET:")?;
@@ -89,6 +254,98 @@ pub enum GenericFileDescriptor {
     File(FileDescription),
     Socket(SocketDescription),
     Pipe,
+    IoUring,
+    /// a dup of an fd that was never seen opened, e.g. because the trace started mid-stream
+    Duplicate(u32),
+    /// memory of another process, moved with process_vm_readv/process_vm_writev
+    ProcessMemory(u32),
+    /// a POSIX message queue, named as in mq_open
+    MessageQueue(String),
+    /// an anonymous file in memory, named as in memfd_create
+    Memfd(String),
+    /// a terminal like /dev/tty or /dev/pts/N, i.e. interactive I/O
+    Tty(String),
+    /// a handle on the process with this pid from pidfd_open
+    Pidfd(u32),
+    /// the fd of another process, given as pid and fd, from pidfd_getfd
+    Imported(u32, u32),
+    /// the random bytes from getrandom, which has no fd of its own
+    Entropy,
+}
+
+impl GenericFileDescriptor {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenericFileDescriptor::File(_) => "file",
+            GenericFileDescriptor::Socket(_) => "socket",
+            GenericFileDescriptor::Pipe => "pipe",
+            GenericFileDescriptor::IoUring => "io_uring",
+            GenericFileDescriptor::Duplicate(_) => "duplicate",
+            GenericFileDescriptor::ProcessMemory(_) => "process_memory",
+            GenericFileDescriptor::MessageQueue(_) => "message_queue",
+            GenericFileDescriptor::Memfd(_) => "memfd",
+            GenericFileDescriptor::Tty(_) => "tty",
+            GenericFileDescriptor::Pidfd(_) => "pidfd",
+            GenericFileDescriptor::Imported(_, _) => "imported",
+            GenericFileDescriptor::Entropy => "entropy",
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self {
+            GenericFileDescriptor::File(_) => Kind::Files,
+            GenericFileDescriptor::Socket(_) => Kind::Sockets,
+            GenericFileDescriptor::Pipe => Kind::Pipes,
+            GenericFileDescriptor::IoUring => Kind::Other,
+            GenericFileDescriptor::Duplicate(_) => Kind::Other,
+            GenericFileDescriptor::ProcessMemory(_) => Kind::Other,
+            GenericFileDescriptor::MessageQueue(_) => Kind::Other,
+            GenericFileDescriptor::Memfd(_) => Kind::Other,
+            GenericFileDescriptor::Tty(_) => Kind::Other,
+            GenericFileDescriptor::Pidfd(_) => Kind::Other,
+            GenericFileDescriptor::Imported(_, _) => Kind::Other,
+            GenericFileDescriptor::Entropy => Kind::Other,
+        }
+    }
+}
+
+impl GenericFileDescriptor {
+    /// A copy with every path and name that could identify a user replaced
+    /// by the tokens of `anonymize`, for `--anonymize`.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::{anonymize, GenericFileDescriptor, SocketDescription};
+    ///
+    /// let mut socket_description = SocketDescription::new();
+    /// socket_description.update_connect(String::from(
+    ///     "{sa_family=AF_UNIX, sun_path=\"/home/alice/private.sock\"}",
+    /// ));
+    /// let socket = GenericFileDescriptor::Socket(socket_description).anonymized();
+    ///
+    /// assert!(!socket.to_string().contains("alice"));
+    /// assert!(socket.to_string().contains(&anonymize("/home/alice/private.sock")));
+    /// ```
+    pub fn anonymized(&self) -> GenericFileDescriptor {
+        match self {
+            GenericFileDescriptor::File(file_description) => {
+                let mut file_description = file_description.clone();
+                file_description.path = anonymize(&file_description.path);
+                GenericFileDescriptor::File(file_description)
+            }
+            GenericFileDescriptor::Socket(socket_description) => {
+                GenericFileDescriptor::Socket(SocketDescription {
+                    bind: anonymize_socket(&socket_description.bind),
+                    connect: anonymize_socket(&socket_description.connect),
+                })
+            }
+            GenericFileDescriptor::MessageQueue(name) => {
+                GenericFileDescriptor::MessageQueue(anonymize(name))
+            }
+            GenericFileDescriptor::Memfd(name) => GenericFileDescriptor::Memfd(anonymize(name)),
+            GenericFileDescriptor::Tty(path) => GenericFileDescriptor::Tty(anonymize(path)),
+            descriptor => descriptor.clone(),
+        }
+    }
 }
 
 impl fmt::Display for GenericFileDescriptor {
@@ -99,6 +356,19 @@ impl fmt::Display for GenericFileDescriptor {
                 write!(f, "{}", socket_description)
             }
             GenericFileDescriptor::Pipe => write!(f, "PIPE"),
+            GenericFileDescriptor::IoUring => write!(f, "IO_URING"),
+            GenericFileDescriptor::Entropy => write!(f, "ENTROPY"),
+            GenericFileDescriptor::Duplicate(oldfd) => {
+                write!(f, "DUP:unresolved duplicate of fd {}", oldfd)
+            }
+            GenericFileDescriptor::ProcessMemory(pid) => write!(f, "PROCESS_MEMORY:pid {}", pid),
+            GenericFileDescriptor::MessageQueue(name) => write!(f, "MQ:{}", name),
+            GenericFileDescriptor::Memfd(name) => write!(f, "MEMFD:{}", name),
+            GenericFileDescriptor::Tty(path) => write!(f, "TTY:{}", path),
+            GenericFileDescriptor::Pidfd(pid) => write!(f, "PIDFD:pid {}", pid),
+            GenericFileDescriptor::Imported(pid, fd) => {
+                write!(f, "IMPORTED:fd {} of pid {}", fd, pid)
+            }
         }
     }
 }
@@ -106,22 +376,111 @@ impl fmt::Display for GenericFileDescriptor {
 #[derive(Clone, Debug)]
 pub struct Summary {
     pub descriptor: GenericFileDescriptor,
+    pub cloexec: bool,
+    pub nonblocking: bool,
     read_freq: HashMap<u64, u64>,
     write_freq: HashMap<u64, u64>,
     read_bytes: u64,
     write_bytes: u64,
+    max_read_op: u64,
+    max_write_op: u64,
     accepted_connections: u64,
+    ring_enters: u64,
+    ring_submitted: u64,
+    fsyncs: u64,
+    fdatasyncs: u64,
+    prefetched: u64,
+    advice: Vec<String>,
+    eof: u64,
+    offset: u64,
+    next_offset: Option<u64>,
+    sequential_ops: u64,
+    random_ops: u64,
+    first_op: Option<u64>,
+    last_op: Option<u64>,
+    // the syscall that opened the fd, for the eviction of idle ones
+    opened_op: Option<u64>,
+    opened: Option<f64>,
+    connected: Option<f64>,
+    first_io: Option<f64>,
+    closed: Option<f64>,
+    errors: BTreeMap<String, u64>,
+    metadata: BTreeMap<String, u64>,
+    restarts: u64,
+    // known for truncated and stat'ed files only, for which writes past the
+    // end can be told apart from writes to what was already there
+    extent: Option<u64>,
+    holes: u64,
 }
 
 impl Summary {
     pub fn new(descriptor: GenericFileDescriptor) -> Summary {
         Summary {
             descriptor,
+            cloexec: false,
+            nonblocking: false,
             read_freq: HashMap::new(),
             write_freq: HashMap::new(),
             read_bytes: 0,
             write_bytes: 0,
+            max_read_op: 0,
+            max_write_op: 0,
             accepted_connections: 0,
+            ring_enters: 0,
+            ring_submitted: 0,
+            fsyncs: 0,
+            fdatasyncs: 0,
+            prefetched: 0,
+            advice: Vec::new(),
+            eof: 0,
+            offset: 0,
+            next_offset: None,
+            sequential_ops: 0,
+            random_ops: 0,
+            first_op: None,
+            last_op: None,
+            opened_op: None,
+            opened: None,
+            connected: None,
+            first_io: None,
+            closed: None,
+            errors: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            restarts: 0,
+            extent: None,
+            holes: 0,
+        }
+    }
+
+    /// Builds a summary from precomputed totals instead of replaying syscalls.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use strace_analyzer::summary::{FileDescription, GenericFileDescriptor, Summary};
+    ///
+    /// let mut read_freq = HashMap::new();
+    /// read_freq.insert(4096, 3);
+    ///
+    /// let descriptor = GenericFileDescriptor::File(FileDescription::new(String::from("/data")));
+    /// let summary = Summary::from_parts(descriptor, read_freq, HashMap::new(), 10000, 0);
+    ///
+    /// assert_eq!(summary.read_ops(), 3);
+    /// assert_eq!(summary.read_bytes(), 10000);
+    /// assert_eq!(summary.write_ops(), 0);
+    /// ```
+    pub fn from_parts(
+        descriptor: GenericFileDescriptor,
+        read_freq: HashMap<u64, u64>,
+        write_freq: HashMap<u64, u64>,
+        read_bytes: u64,
+        write_bytes: u64,
+    ) -> Summary {
+        Summary {
+            read_freq,
+            write_freq,
+            read_bytes,
+            write_bytes,
+            ..Summary::new(descriptor)
         }
     }
 
@@ -137,61 +496,798 @@ impl Summary {
         Summary::new(GenericFileDescriptor::Socket(SocketDescription::new()))
     }
 
+    /// A POSIX message queue, the messages sent count as writes and the
+    /// received ones as reads.
+    pub fn message_queue(name: String) -> Summary {
+        Summary::new(GenericFileDescriptor::MessageQueue(name))
+    }
+
+    /// An anonymous file in memory from `memfd_create`, e.g. a scratch buffer
+    /// or memory shared with another process, labeled with its name.
+    pub fn memfd(name: String) -> Summary {
+        Summary::new(GenericFileDescriptor::Memfd(name))
+    }
+
+    /// The bytes of all `getrandom` calls of the run, the same entropy a read
+    /// from `/dev/urandom` gets, which is tagged as such.
+    pub fn entropy() -> Summary {
+        Summary::new(GenericFileDescriptor::Entropy)
+    }
+
+    /// A handle on another process from `pidfd_open`. `pidfd_getfd` imports
+    /// the fds of that process, labeled with their pid and fd.
+    pub fn pidfd(pid: u32) -> Summary {
+        Summary::new(GenericFileDescriptor::Pidfd(pid))
+    }
+
+    pub fn imported(pid: u32, fd: u32) -> Summary {
+        Summary::new(GenericFileDescriptor::Imported(pid, fd))
+    }
+
+    /// A terminal, `path` being one of the paths `is_tty` recognizes. Hidden
+    /// like the standard streams unless `--ttys` is given.
+    pub fn tty(path: String) -> Summary {
+        Summary::new(GenericFileDescriptor::Tty(path))
+    }
+
+    pub fn io_uring() -> Summary {
+        Summary::new(GenericFileDescriptor::IoUring)
+    }
+
     pub fn reset(&mut self) {
         self.read_freq.clear();
         self.write_freq.clear();
         self.read_bytes = 0;
         self.write_bytes = 0;
+        self.max_read_op = 0;
+        self.max_write_op = 0;
         self.accepted_connections = 0;
+        self.ring_enters = 0;
+        self.ring_submitted = 0;
+        self.fsyncs = 0;
+        self.fdatasyncs = 0;
+        self.prefetched = 0;
+        self.advice.clear();
+        self.eof = 0;
+        self.sequential_ops = 0;
+        self.random_ops = 0;
+        self.first_op = None;
+        self.last_op = None;
+        self.first_io = None;
+        self.errors.clear();
+        self.metadata.clear();
+        self.restarts = 0;
+        self.extent = None;
+        self.holes = 0;
+    }
+
+    pub fn set_cloexec(&mut self) {
+        self.cloexec = true;
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
     }
 
-    pub fn update_read(&mut self, op_size: u64, bytes: u64) {
+    pub fn set_opened(&mut self, time: Option<f64>) {
+        self.opened = time;
+    }
+
+    pub fn set_opened_op(&mut self, seq: u64) {
+        self.opened_op = Some(seq);
+    }
+
+    pub fn set_connected(&mut self, time: Option<f64>) {
+        self.connected = time;
+    }
+
+    pub fn update_first_io(&mut self, time: Option<f64>) {
+        if self.first_io.is_none() {
+            self.first_io = time;
+        }
+    }
+
+    /// The seconds between the open of the fd, or the connect of a socket,
+    /// and its first read or write. Only known for timestamped traces.
+    pub fn first_io_latency(&self) -> Option<f64> {
+        Some(self.first_io? - self.connected.or(self.opened)?)
+    }
+
+    pub fn set_closed(&mut self, time: Option<f64>) {
+        self.closed = time;
+    }
+
+    // only known for timestamped traces and fds that were closed explicitly
+    pub fn lifetime(&self) -> Option<f64> {
+        Some(self.closed? - self.opened?)
+    }
+
+    fn update_seq(&mut self, seq: u64) {
+        if self.first_op.is_none() {
+            self.first_op = Some(seq);
+        }
+        self.last_op = Some(seq);
+    }
+
+    pub fn update_read(&mut self, op_size: u64, bytes: u64, seq: u64) {
+        self.update_seq(seq);
+
+        // a zero-byte read marks end-of-file, not an actual data transfer,
+        // unless nothing was asked for
+        if op_size > 0 && bytes == 0 {
+            self.eof += 1;
+            return;
+        }
+
         let freq = self.read_freq.entry(op_size).or_insert(0);
         *freq += 1;
         self.read_bytes += bytes;
+        self.max_read_op = self.max_read_op.max(bytes);
     }
 
-    pub fn update_write(&mut self, op_size: u64, bytes: u64) {
+    pub fn update_write(&mut self, op_size: u64, bytes: u64, seq: u64) {
         let freq = self.write_freq.entry(op_size).or_insert(0);
         *freq += 1;
         self.write_bytes += bytes;
+        self.max_write_op = self.max_write_op.max(bytes);
+        self.update_seq(seq);
+    }
+
+    pub fn update_offset(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    // an op is sequential if it starts where the previous one ended,
+    // positioned I/O doesn't move the file pointer
+    pub fn update_position(&mut self, offset: Option<u64>, bytes: u64) {
+        let position = offset.unwrap_or(self.offset);
+
+        match self.next_offset {
+            Some(next_offset) if next_offset == position => self.sequential_ops += 1,
+            Some(_) => self.random_ops += 1,
+            None => (),
+        }
+
+        self.next_offset = Some(position + bytes);
+
+        if offset.is_none() {
+            self.offset += bytes;
+        }
+    }
+
+    pub fn set_truncated(&mut self) {
+        self.extent = Some(0);
+    }
+
+    // a write starting past the end of the file leaves a hole, i.e. the file
+    // is sparse
+    pub fn update_extent(&mut self, offset: Option<u64>, bytes: u64) {
+        let position = offset.unwrap_or(self.offset);
+
+        if let Some(extent) = self.extent {
+            if position > extent {
+                self.holes += position - extent;
+            }
+
+            self.extent = Some(extent.max(position + bytes));
+        }
+    }
+
+    /// The size the writes imply for a sparse file and the bytes skipped by
+    /// seeking past its end before writing, `None` if no write did. Only
+    /// files that were truncated when opened or stat'ed have a known end.
+    pub fn sparse(&self) -> Option<(u64, u64)> {
+        if self.holes == 0 {
+            None
+        } else {
+            Some((self.extent?, self.holes))
+        }
     }
 
     pub fn update_accept(&mut self) {
         self.accepted_connections += 1;
     }
 
-    pub fn show(&self, config: &Config) {
+    pub fn update_sync(&mut self, syscall: &str) {
+        match syscall {
+            "fsync" => self.fsyncs += 1,
+            _ => self.fdatasyncs += 1,
+        }
+    }
+
+    pub fn update_metadata(&mut self, syscall: &str) {
+        *self.metadata.entry(String::from(syscall)).or_insert(0) += 1;
+    }
+
+    // interrupted by a signal and restarted by the kernel, ERESTARTSYS and
+    // friends never reach the program, so they're no errors
+    pub fn update_restart(&mut self) {
+        self.restarts += 1;
+    }
+
+    pub fn update_error(&mut self, errno: &str) {
+        *self.errors.entry(String::from(errno)).or_insert(0) += 1;
+    }
+
+    /// Returns how many I/O syscalls failed per errno.
+    pub fn errors(&self) -> &BTreeMap<String, u64> {
+        &self.errors
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    /// The share of I/O attempts that failed with EINTR or EAGAIN or were
+    /// restarted after a signal and had to be retried, `None` without any
+    /// such failures.
+    pub fn retry_rate(&self) -> Option<f64> {
+        let retries: u64 = ["EINTR", "EAGAIN"]
+            .iter()
+            .filter_map(|errno| self.errors.get(*errno))
+            .sum::<u64>()
+            + self.restarts;
+
+        if retries == 0 {
+            return None;
+        }
+
+        let attempts = retries + self.read_ops() + self.write_ops() + self.eof;
+        Some(retries as f64 / attempts as f64)
+    }
+
+    /// Follows a rename of the file from `from` to `to`, or the swap of both
+    /// names with `exchange`.
+    pub fn rename(&mut self, from: &str, to: &str, exchange: bool) {
+        if let GenericFileDescriptor::File(file_description) = &mut self.descriptor {
+            if file_description.path == from {
+                file_description.path = String::from(to);
+            } else if exchange && file_description.path == to {
+                file_description.path = String::from(from);
+            }
+        }
+    }
+
+    pub fn update_size(&mut self, size: u64) {
+        if let GenericFileDescriptor::File(file_description) = &mut self.descriptor {
+            file_description.size = Some(size);
+            self.extent = Some(self.extent.unwrap_or(0).max(size));
+        }
+    }
+
+    pub fn set_filesystem(&mut self, f_type: &str, block_size: u64) {
+        if let GenericFileDescriptor::File(file_description) = &mut self.descriptor {
+            file_description.filesystem = Some(filesystem(f_type));
+            file_description.block_size = Some(block_size);
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: Option<AccessMode>) {
+        if let GenericFileDescriptor::File(file_description) = &mut self.descriptor {
+            file_description.mode = mode;
+        }
+    }
+
+    pub fn update_readahead(&mut self, count: u64) {
+        self.prefetched += count;
+    }
+
+    // the distinct advice given, in the order it was first seen
+    pub fn update_advice(&mut self, advice: &str) {
+        if !self.advice.iter().any(|a| a == advice) {
+            self.advice.push(String::from(advice));
+        }
+    }
+
+    pub fn update_ring_enter(&mut self, submitted: u64, seq: u64) {
+        self.ring_enters += 1;
+        self.ring_submitted += submitted;
+        self.update_seq(seq);
+    }
+
+    pub fn merge(&mut self, other: &Summary) {
+        for (op_size, freq) in &other.read_freq {
+            *self.read_freq.entry(*op_size).or_insert(0) += freq;
+        }
+        for (op_size, freq) in &other.write_freq {
+            *self.write_freq.entry(*op_size).or_insert(0) += freq;
+        }
+        self.read_bytes += other.read_bytes;
+        self.write_bytes += other.write_bytes;
+        self.max_read_op = self.max_read_op.max(other.max_read_op);
+        self.max_write_op = self.max_write_op.max(other.max_write_op);
+        self.accepted_connections += other.accepted_connections;
+        self.ring_enters += other.ring_enters;
+        self.ring_submitted += other.ring_submitted;
+        self.fsyncs += other.fsyncs;
+        self.fdatasyncs += other.fdatasyncs;
+        self.prefetched += other.prefetched;
+        for advice in &other.advice {
+            self.update_advice(advice);
+        }
+        self.eof += other.eof;
+        self.sequential_ops += other.sequential_ops;
+        self.random_ops += other.random_ops;
+        self.first_op = match (self.first_op, other.first_op) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_op = self.last_op.max(other.last_op);
+        self.opened_op = self.opened_op.max(other.opened_op);
+        for (errno, count) in &other.errors {
+            *self.errors.entry(errno.clone()).or_insert(0) += count;
+        }
+        for (syscall, count) in &other.metadata {
+            *self.metadata.entry(syscall.clone()).or_insert(0) += count;
+        }
+        self.restarts += other.restarts;
+        self.extent = self.extent.max(other.extent);
+        self.holes += other.holes;
+    }
+
+    /// Returns how many reads were issued per requested op size.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let mut summary = Summary::file(String::from("/etc/hosts"));
+    /// summary.update_read(4096, 4096, 1);
+    /// summary.update_read(4096, 100, 2);
+    ///
+    /// for (op_size, ops) in summary.read_distribution() {
+    ///     assert_eq!((*op_size, *ops), (4096, 2));
+    /// }
+    /// ```
+    pub fn read_distribution(&self) -> &HashMap<u64, u64> {
+        &self.read_freq
+    }
+
+    /// Returns how many writes were issued per requested op size.
+    pub fn write_distribution(&self) -> &HashMap<u64, u64> {
+        &self.write_freq
+    }
+
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes
+    }
+
+    pub fn write_bytes(&self) -> u64 {
+        self.write_bytes
+    }
+
+    pub fn read_ops(&self) -> u64 {
+        self.read_freq.values().sum()
+    }
+
+    pub fn write_ops(&self) -> u64 {
+        self.write_freq.values().sum()
+    }
+
+    /// Returns how often the metadata of the file was changed per syscall,
+    /// none of which count as I/O.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let mut summary = Summary::file(String::from("/srv/data"));
+    /// summary.update_metadata("fchmod");
+    /// summary.update_metadata("fchown");
+    ///
+    /// assert_eq!(summary.metadata().get("fchmod"), Some(&1));
+    /// assert_eq!(summary.metadata().get("fchown"), Some(&1));
+    /// assert_eq!(summary.write_bytes(), 0);
+    /// ```
+    pub fn metadata(&self) -> &BTreeMap<String, u64> {
+        &self.metadata
+    }
+
+    /// Returns the weight of the summary for `--sort`: the bytes read and
+    /// written, or the number of read and write syscalls regardless of their
+    /// size.
+    ///
+    /// ```
+    /// use strace_analyzer::config::SortKey;
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let mut tiny = Summary::file(String::from("/srv/tiny"));
+    /// for _ in 0..100 {
+    ///     tiny.update_read(1, 1, 1);
+    /// }
+    ///
+    /// let mut big = Summary::file(String::from("/srv/big"));
+    /// big.update_read(1048576, 1048576, 1);
+    ///
+    /// assert!(tiny.weight(SortKey::Ops) > big.weight(SortKey::Ops));
+    /// assert!(tiny.weight(SortKey::Bytes) < big.weight(SortKey::Bytes));
+    /// ```
+    pub fn weight(&self, key: SortKey) -> u64 {
+        match key {
+            SortKey::Closed => 0,
+            SortKey::Bytes => self.read_bytes + self.write_bytes,
+            SortKey::Ops => self.read_ops() + self.write_ops(),
+        }
+    }
+
+    pub fn last_op(&self) -> Option<u64> {
+        self.last_op
+    }
+
+    /// The last syscall that opened the fd or transferred data over it,
+    /// `None` for fds like the standard streams that saw neither.
+    pub fn last_active(&self) -> Option<u64> {
+        self.last_op.max(self.opened_op)
+    }
+
+    pub fn read_op_sizes(&self) -> Option<(u64, u64, u64)> {
+        op_sizes(&self.read_freq)
+    }
+
+    pub fn write_op_sizes(&self) -> Option<(u64, u64, u64)> {
+        op_sizes(&self.write_freq)
+    }
+
+    pub fn ratio(&self) -> String {
+        match (self.read_bytes, self.write_bytes) {
+            (_, 0) => String::from("∞"),
+            (0, _) => String::from("0"),
+            (read, write) => format!("{:.2}", read as f64 / write as f64),
+        }
+    }
+
+    /// Returns the bytes actually transferred relative to the bytes requested
+    /// by the reads and by the writes, `None` without such ops. Far below 1
+    /// means short reads or partial writes, e.g. because of backpressure.
+    ///
+    /// ```
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let mut short = Summary::file(String::from("/srv/short"));
+    /// short.update_read(4096, 1024, 1);
+    /// short.update_read(4096, 1024, 2);
+    ///
+    /// let mut full = Summary::file(String::from("/srv/full"));
+    /// full.update_read(4096, 4096, 1);
+    /// full.update_write(512, 512, 2);
+    ///
+    /// assert_eq!(short.transfer_ratio(), (Some(0.25), None));
+    /// assert_eq!(full.transfer_ratio(), (Some(1.0), Some(1.0)));
+    /// ```
+    pub fn transfer_ratio(&self) -> (Option<f64>, Option<f64>) {
+        let ratio = |freq: &HashMap<u64, u64>, bytes: u64| {
+            let requested: u64 = freq.iter().map(|(op_size, n)| op_size * n).sum();
+
+            if requested == 0 {
+                None
+            } else {
+                Some(bytes as f64 / requested as f64)
+            }
+        };
+
+        (
+            ratio(&self.read_freq, self.read_bytes),
+            ratio(&self.write_freq, self.write_bytes),
+        )
+    }
+
+    /// Rates the I/O of a file from 0 to 100 for `--score`, `None` for other
+    /// fds or without any reads and writes. This is an opinionated heuristic,
+    /// the sum of:
+    ///
+    /// - up to 40 points for the average op size, nothing for 512 bytes or
+    ///   less and all of them for 128 KiB or more, log-scaled in between
+    /// - up to 30 points for the share of sequential ops, all of them if the
+    ///   access pattern is unknown
+    /// - up to 30 points for the bytes transferred relative to the bytes
+    ///   requested, i.e. the fewer short reads and writes the better
+    ///
+    /// ```
+    /// use strace_analyzer::summary::Summary;
+    ///
+    /// let mut good = Summary::file(String::from("/srv/good"));
+    /// for i in 0..16 {
+    ///     good.update_position(None, 131072);
+    ///     good.update_read(131072, 131072, i);
+    /// }
+    ///
+    /// let mut bad = Summary::file(String::from("/srv/bad"));
+    /// for i in 0..16 {
+    ///     bad.update_position(Some((i * 7919) % 16 * 4096), 64);
+    ///     bad.update_read(4096, 64, i);
+    /// }
+    ///
+    /// assert_eq!(good.score(), Some(100));
+    /// assert!(bad.score().unwrap() < 25);
+    /// assert_eq!(Summary::pipe().score(), None);
+    /// ```
+    pub fn score(&self) -> Option<u64> {
+        let ops = match self.descriptor {
+            GenericFileDescriptor::File(_) => self.read_ops() + self.write_ops(),
+            _ => return None,
+        };
+
+        if ops == 0 {
+            return None;
+        }
+
+        let bytes = self.read_bytes + self.write_bytes;
+        let size = (bytes as f64 / ops as f64).max(1.0).log2();
+        let size = ((size - 9.0) / 8.0).clamp(0.0, 1.0);
+
+        let positioned = self.sequential_ops + self.random_ops;
+        let sequential = if positioned == 0 {
+            1.0
+        } else {
+            self.sequential_ops as f64 / positioned as f64
+        };
+
+        let requested: u64 = self
+            .read_freq
+            .iter()
+            .chain(self.write_freq.iter())
+            .map(|(op_size, n)| op_size * n)
+            .sum();
+        let transferred = if requested == 0 {
+            1.0
+        } else {
+            (bytes as f64 / requested as f64).min(1.0)
+        };
+
+        Some((40.0 * size + 30.0 * sequential + 30.0 * transferred).round() as u64)
+    }
+
+    /// Whether the I/O is random bytes from the kernel, i.e. `getrandom` or
+    /// reads from `/dev/urandom` and `/dev/random`.
+    pub fn is_entropy(&self) -> bool {
+        match &self.descriptor {
+            GenericFileDescriptor::Entropy => true,
+            GenericFileDescriptor::File(file_description) => {
+                file_description.path == "/dev/urandom" || file_description.path == "/dev/random"
+            }
+            _ => false,
+        }
+    }
+
+    fn special_device(&self) -> Option<&'static str> {
+        if let GenericFileDescriptor::File(file_description) = &self.descriptor {
+            match file_description.path.as_str() {
+                "/dev/null" => Some("discarded, not real I/O"),
+                "/dev/zero" => Some("synthetic, not real I/O"),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let (size, mode, filesystem, block_size) = match &self.descriptor {
+            GenericFileDescriptor::File(file_description) => (
+                file_description.size,
+                file_description.mode,
+                file_description.filesystem.as_deref(),
+                file_description.block_size,
+            ),
+            _ => (None, None, None, None),
+        };
+
+        let (path, bind, connect) = match &self.descriptor {
+            GenericFileDescriptor::File(file_description) => {
+                (file_description.path.clone(), "", "")
+            }
+            GenericFileDescriptor::Socket(socket_description) => (
+                String::new(),
+                socket_description.bind.as_str(),
+                socket_description.connect.as_str(),
+            ),
+            GenericFileDescriptor::Pipe
+            | GenericFileDescriptor::IoUring
+            | GenericFileDescriptor::Entropy => (String::new(), "", ""),
+            // the path holds the fd that was duplicated
+            GenericFileDescriptor::Duplicate(oldfd) => (oldfd.to_string(), "", ""),
+            // and the path holds the peer pid
+            GenericFileDescriptor::ProcessMemory(pid) | GenericFileDescriptor::Pidfd(pid) => {
+                (pid.to_string(), "", "")
+            }
+            GenericFileDescriptor::Imported(pid, fd) => (format!("{}:{}", pid, fd), "", ""),
+            GenericFileDescriptor::MessageQueue(name)
+            | GenericFileDescriptor::Memfd(name)
+            | GenericFileDescriptor::Tty(name) => (name.clone(), "", ""),
+        };
+
+        let advice: Vec<String> = self.advice.iter().map(|a| json::escape(a)).collect();
+
+        format!(
+            "{{\"kind\":\"{}\",\"path\":{},\"size\":{},\"mode\":{},\
+             \"filesystem\":{},\"block_size\":{},\"bind\":{},\"connect\":{},\
+             \"nonblocking\":{},\"read_freq\":{},\"write_freq\":{},\
+             \"read_bytes\":{},\"write_bytes\":{},\"max_read_op\":{},\"max_write_op\":{},\
+             \"accepted_connections\":{},\
+             \"ring_enters\":{},\"ring_submitted\":{},\"fsyncs\":{},\"fdatasyncs\":{},\
+             \"prefetched\":{},\"advice\":[{}],\"eof\":{},\
+             \"sequential_ops\":{},\"random_ops\":{},\"first_op\":{},\"last_op\":{},\
+             \"opened\":{},\"connected\":{},\"first_io\":{},\"closed\":{},\"errors\":{},\"metadata\":{},\
+             \"restarts\":{},\"extent\":{},\"holes\":{}}}",
+            self.descriptor.name(),
+            json::escape(&path),
+            option_to_json(size),
+            mode.map_or(String::from("null"), |mode| json::escape(mode.name())),
+            filesystem.map_or(String::from("null"), json::escape),
+            option_to_json(block_size),
+            json::escape(bind),
+            json::escape(connect),
+            self.nonblocking,
+            freq_to_json(&self.read_freq),
+            freq_to_json(&self.write_freq),
+            self.read_bytes,
+            self.write_bytes,
+            self.max_read_op,
+            self.max_write_op,
+            self.accepted_connections,
+            self.ring_enters,
+            self.ring_submitted,
+            self.fsyncs,
+            self.fdatasyncs,
+            self.prefetched,
+            advice.join(","),
+            self.eof,
+            self.sequential_ops,
+            self.random_ops,
+            option_to_json(self.first_op),
+            option_to_json(self.last_op),
+            option_to_json(self.opened),
+            option_to_json(self.connected),
+            option_to_json(self.first_io),
+            option_to_json(self.closed),
+            counts_to_json(&self.errors),
+            counts_to_json(&self.metadata),
+            self.restarts,
+            option_to_json(self.extent),
+            self.holes,
+        )
+    }
+
+    pub fn from_json(value: &Value) -> Option<Summary> {
+        let text = |key| value.get(key).and_then(Value::as_str);
+        let number = |key| value.get(key).and_then(Value::as_u64);
+
+        let descriptor = match text("kind")? {
+            "file" => GenericFileDescriptor::File(FileDescription {
+                path: String::from(text("path")?),
+                size: number("size"),
+                mode: text("mode").and_then(AccessMode::from_name),
+                filesystem: text("filesystem").map(String::from),
+                block_size: number("block_size"),
+            }),
+            "socket" => GenericFileDescriptor::Socket(SocketDescription {
+                bind: String::from(text("bind")?),
+                connect: String::from(text("connect")?),
+            }),
+            "pipe" => GenericFileDescriptor::Pipe,
+            "io_uring" => GenericFileDescriptor::IoUring,
+            "entropy" => GenericFileDescriptor::Entropy,
+            "duplicate" => GenericFileDescriptor::Duplicate(text("path")?.parse().ok()?),
+            "process_memory" => GenericFileDescriptor::ProcessMemory(text("path")?.parse().ok()?),
+            "message_queue" => GenericFileDescriptor::MessageQueue(String::from(text("path")?)),
+            "memfd" => GenericFileDescriptor::Memfd(String::from(text("path")?)),
+            "tty" => GenericFileDescriptor::Tty(String::from(text("path")?)),
+            "pidfd" => GenericFileDescriptor::Pidfd(text("path")?.parse().ok()?),
+            "imported" => {
+                let mut source = text("path")?.splitn(2, ':');
+                let pid = source.next()?.parse().ok()?;
+                let fd = source.next()?.parse().ok()?;
+                GenericFileDescriptor::Imported(pid, fd)
+            }
+            _ => return None,
+        };
+
+        let mut summary = Summary::from_parts(
+            descriptor,
+            freq_from_json(value.get("read_freq"))?,
+            freq_from_json(value.get("write_freq"))?,
+            number("read_bytes")?,
+            number("write_bytes")?,
+        );
+
+        summary.nonblocking = value.get("nonblocking").and_then(Value::as_bool)?;
+        summary.max_read_op = number("max_read_op")?;
+        summary.max_write_op = number("max_write_op")?;
+        summary.accepted_connections = number("accepted_connections")?;
+        summary.ring_enters = number("ring_enters")?;
+        summary.ring_submitted = number("ring_submitted")?;
+        summary.fsyncs = number("fsyncs")?;
+        summary.fdatasyncs = number("fdatasyncs")?;
+        summary.prefetched = number("prefetched")?;
+        for advice in value.get("advice")?.as_array()? {
+            summary.update_advice(advice.as_str()?);
+        }
+        summary.eof = number("eof")?;
+        summary.sequential_ops = number("sequential_ops")?;
+        summary.random_ops = number("random_ops")?;
+        summary.first_op = number("first_op");
+        summary.last_op = number("last_op");
+        summary.opened = value.get("opened").and_then(Value::as_f64);
+        summary.connected = value.get("connected").and_then(Value::as_f64);
+        summary.first_io = value.get("first_io").and_then(Value::as_f64);
+        summary.closed = value.get("closed").and_then(Value::as_f64);
+        for (errno, count) in value.get("errors")?.as_object()? {
+            summary.errors.insert(errno.clone(), count.as_u64()?);
+        }
+        for (syscall, count) in value.get("metadata")?.as_object()? {
+            summary.metadata.insert(syscall.clone(), count.as_u64()?);
+        }
+        summary.restarts = number("restarts")?;
+        summary.extent = number("extent");
+        summary.holes = number("holes")?;
+
+        Some(summary)
+    }
+
+    /// Whether the summary is worth showing, hiding system files and
+    /// descriptors without I/O unless asked for.
+    pub fn visible(&self, config: &Config) -> bool {
         if !config.verbose {
             if let GenericFileDescriptor::File(file_description) = &self.descriptor {
-                if file_description.path.starts_with("/bin/")
-                    || file_description.path == "/dev/null"
-                    || file_description.path.starts_with("/etc/")
-                    || file_description.path.starts_with("/lib/")
-                    || file_description.path.starts_with("/lib64/")
-                    || file_description.path.starts_with("/opt/")
-                    || file_description.path.starts_with("/proc/")
-                    || file_description.path.starts_with("/run/")
-                    || file_description.path.starts_with("/sbin/")
-                    || file_description.path.starts_with("/sys/")
-                    || file_description.path.starts_with("/tmp/")
-                    || file_description.path.starts_with("/usr/")
-                    || file_description.path == "STDOUT"
-                    || file_description.path == "STDERR"
-                    || file_description.path == "STDIN"
-                    || file_description.path == "DUP"
+                let special = config.note_special_devices && self.special_device().is_some();
+
+                let default_filter = !config.no_default_filter
+                    && (file_description.path.starts_with("/bin/")
+                        || file_description.path == "/dev/null"
+                        || file_description.path.starts_with("/etc/")
+                        || file_description.path.starts_with("/lib/")
+                        || file_description.path.starts_with("/lib64/")
+                        || file_description.path.starts_with("/opt/")
+                        || file_description.path.starts_with("/proc/")
+                        || file_description.path.starts_with("/run/")
+                        || file_description.path.starts_with("/sbin/")
+                        || file_description.path.starts_with("/sys/")
+                        || file_description.path.starts_with("/tmp/")
+                        || file_description.path.starts_with("/usr/"));
+
+                if !special
+                    && (default_filter
+                        || file_description.path == "STDOUT"
+                        || file_description.path == "STDERR"
+                        || file_description.path == "STDIN")
                 {
-                    return;
+                    return false;
                 }
             }
 
             // Only print files/sockets which have useful information
             match &self.descriptor {
-                GenericFileDescriptor::Pipe => return,
+                GenericFileDescriptor::Pipe | GenericFileDescriptor::Duplicate(_) => return false,
                 GenericFileDescriptor::File(_) => {
+                    let synced = self.fsyncs != 0 || self.fdatasyncs != 0;
+                    let changed = !self.metadata.is_empty();
+
+                    if self.read_freq.is_empty()
+                        && self.write_freq.is_empty()
+                        && !(config.sync && synced)
+                        && !(config.metadata && changed)
+                        && !config.include_zero_io
+                    {
+                        debug(String::from("no I/O"), config);
+                        return false;
+                    }
+                }
+                GenericFileDescriptor::ProcessMemory(_) => {}
+                // interactive like the standard streams, so hidden the same way
+                GenericFileDescriptor::Tty(_) if !config.ttys => return false,
+                GenericFileDescriptor::MessageQueue(_)
+                | GenericFileDescriptor::Memfd(_)
+                | GenericFileDescriptor::Entropy
+                | GenericFileDescriptor::Tty(_)
+                | GenericFileDescriptor::Pidfd(_)
+                | GenericFileDescriptor::Imported(_, _) => {
                     if self.read_freq.is_empty() && self.write_freq.is_empty() {
                         debug(String::from("no I/O"), config);
-                        return;
+                        return false;
+                    }
+                }
+                GenericFileDescriptor::IoUring => {
+                    if self.ring_enters == 0 {
+                        debug(String::from("no I/O"), config);
+                        return false;
                     }
                 }
                 GenericFileDescriptor::Socket(socket_description) => {
@@ -202,48 +1298,851 @@ impl Summary {
                             && socket_description.connect.is_empty())
                     {
                         debug(String::from("no I/O"), config);
-                        return;
+                        return false;
                     }
                 }
             }
         }
 
-        println!("{}", self.descriptor);
+        true
+    }
+
+    /// The descriptor as shown to the user, anonymized with `--anonymize`.
+    pub fn label(&self, config: &Config) -> String {
+        let anonymized;
+        let descriptor = if config.anonymize {
+            anonymized = self.descriptor.anonymized();
+            &anonymized
+        } else {
+            &self.descriptor
+        };
+
+        match descriptor {
+            GenericFileDescriptor::Socket(_) if config.resolve_ports => {
+                resolve_ports(&descriptor.to_string())
+            }
+            descriptor => descriptor.to_string(),
+        }
+    }
+
+    /// A single value for machine-readable output, `name` is one of `FIELDS`.
+    pub fn field(&self, name: &str, config: &Config) -> Value {
+        let number = |n: u64| Value::Number(n.to_string());
+        let option = |n: Option<u64>| n.map_or(Value::Null, number);
+
+        match name {
+            "kind" => Value::String(String::from(self.descriptor.name())),
+            "path" => match &self.descriptor {
+                GenericFileDescriptor::File(file_description) => {
+                    Value::String(render_path(&file_description.path, config))
+                }
+                _ => Value::String(self.label(config).replace("\n\t", " ")),
+            },
+            "read_bytes" => number(self.read_bytes),
+            "read_ops" => number(self.read_ops()),
+            "write_bytes" => number(self.write_bytes),
+            "write_ops" => number(self.write_ops()),
+            "max_read_op" => number(self.max_read_op),
+            "max_write_op" => number(self.max_write_op),
+            "eof" => number(self.eof),
+            "accepted" => number(self.accepted_connections),
+            "sequential_ops" => number(self.sequential_ops),
+            "random_ops" => number(self.random_ops),
+            "first_op" => option(self.first_op),
+            "last_op" => option(self.last_op),
+            _ => Value::Null,
+        }
+    }
+
+    /// Prints the summary, `totals` are the read and write bytes of the whole
+    /// run and are only used for `--percent`.
+    pub fn show(&self, config: &Config, totals: (u64, u64)) {
+        if !self.visible(config) {
+            return;
+        }
+
+        let mut descriptor = self.label(config);
+
+        if let Some(width) = config.path_width {
+            descriptor = truncate(&descriptor, width);
+        }
+
+        if let GenericFileDescriptor::File(_) = self.descriptor {
+            if config.include_zero_io && self.read_freq.is_empty() && self.write_freq.is_empty() {
+                descriptor.push_str(" (no I/O)");
+            }
+        }
+
+        if let GenericFileDescriptor::File(_) = self.descriptor {
+            if self.is_entropy() {
+                descriptor.push_str(" (entropy)");
+            }
+        }
+
+        if let Some(note) = self
+            .special_device()
+            .filter(|_| config.note_special_devices)
+        {
+            descriptor = format!("{} ({})", descriptor, note);
+        }
+
+        if config.compact {
+            println!(
+                "{} R:{}/{}ops W:{}/{}ops",
+                descriptor.replace("\n\t", " "),
+                humanize(self.read_bytes, config),
+                self.read_ops(),
+                humanize(self.write_bytes, config),
+                self.write_ops(),
+            );
+            return;
+        }
+
+        println!("{}", descriptor);
 
         if !self.read_freq.is_empty() {
             let (op_size, _) = self.read_freq.iter().max().unwrap();
-            let n_ops: u64 = self.read_freq.values().sum();
+            let n_ops = self.read_ops();
 
             println!(
-                "\tread {} with {} ops ({} / op)",
-                humanize(self.read_bytes),
+                "\tread {} with {} ops ({} / op){}",
+                humanize(self.read_bytes, config),
                 n_ops,
-                humanize(*op_size),
+                humanize(*op_size, config),
+                share(self.read_bytes, totals.0, config),
             );
         }
 
         if !self.write_freq.is_empty() {
             let (op_size, _) = self.write_freq.iter().max().unwrap();
-            let n_ops: u64 = self.write_freq.values().sum();
+            let n_ops = self.write_ops();
 
             println!(
-                "\twrite {} with {} ops ({} / op)",
-                humanize(self.write_bytes),
+                "\twrite {} with {} ops ({} / op){}",
+                humanize(self.write_bytes, config),
                 n_ops,
-                humanize(*op_size),
+                humanize(*op_size, config),
+                share(self.write_bytes, totals.1, config),
+            );
+        }
+
+        if self.ring_enters != 0 {
+            println!(
+                "\tentered {} times, submitted {} entries",
+                self.ring_enters, self.ring_submitted,
+            );
+        }
+
+        if config.op_sizes {
+            if let Some((min, avg, max)) = self.read_op_sizes() {
+                println!(
+                    "\tread op sizes: min {} / avg {} / max {}",
+                    humanize(min, config),
+                    humanize(avg, config),
+                    humanize(max, config),
+                );
+            }
+
+            if let Some((min, avg, max)) = self.write_op_sizes() {
+                println!(
+                    "\twrite op sizes: min {} / avg {} / max {}",
+                    humanize(min, config),
+                    humanize(avg, config),
+                    humanize(max, config),
+                );
+            }
+
+            let mut rows = Vec::new();
+            for (op, freq) in &[("read", &self.read_freq), ("write", &self.write_freq)] {
+                let mut freq: Vec<(&u64, &u64)> = freq.iter().collect();
+                freq.sort();
+
+                for (op_size, ops) in freq {
+                    rows.push((
+                        format!("{} {}: {} ops", op, humanize(*op_size, config), ops),
+                        *ops,
+                    ));
+                }
+            }
+            show_histogram(&rows, 2, config);
+        }
+
+        if config.transfer_ratio {
+            let (read, write) = self.transfer_ratio();
+            let ratios: Vec<String> = vec![("read", read), ("write", write)]
+                .into_iter()
+                .filter_map(|(op, ratio)| ratio.map(|ratio| format!("{} {:.2}", op, ratio)))
+                .collect();
+
+            if !ratios.is_empty() {
+                println!("\ttransferred of requested: {}", ratios.join(", "));
+            }
+        }
+
+        if config.score {
+            if let Some(score) = self.score() {
+                println!("\tscore: {}/100", score);
+            }
+        }
+
+        if config.sync && (self.fsyncs != 0 || self.fdatasyncs != 0) {
+            println!(
+                "\tsync: {} fsync, {} fdatasync",
+                self.fsyncs, self.fdatasyncs,
             );
         }
 
+        if config.metadata && !self.metadata.is_empty() {
+            let metadata: Vec<String> = self
+                .metadata
+                .iter()
+                .map(|(syscall, count)| format!("{} {}", count, syscall))
+                .collect();
+
+            println!("\tmetadata: {}", metadata.join(", "));
+        }
+
         if self.accepted_connections != 0 {
             println!("\taccepted: {} connections", self.accepted_connections,);
         }
+
+        if config.verbose && (self.sequential_ops != 0 || self.random_ops != 0) {
+            if let GenericFileDescriptor::File(_) = self.descriptor {
+                println!(
+                    "\taccess: {} sequential / {} random ops",
+                    self.sequential_ops, self.random_ops,
+                );
+            }
+        }
+
+        // bytes actually transferred, unlike the requested op sizes above
+        if config.verbose && (self.max_read_op != 0 || self.max_write_op != 0) {
+            println!(
+                "\tlargest op: read {}, write {}",
+                humanize(self.max_read_op, config),
+                humanize(self.max_write_op, config),
+            );
+        }
+
+        if config.verbose && self.read_bytes != 0 {
+            if let GenericFileDescriptor::File(FileDescription {
+                size: Some(size), ..
+            }) = self.descriptor
+            {
+                if size != 0 {
+                    println!(
+                        "\tread coverage: {:.1}% of {}",
+                        self.read_bytes as f64 * 100.0 / size as f64,
+                        humanize(size, config),
+                    );
+                }
+            }
+        }
+
+        if let GenericFileDescriptor::File(FileDescription {
+            filesystem: Some(filesystem),
+            block_size: Some(block_size),
+            ..
+        }) = &self.descriptor
+        {
+            let note = match filesystem.as_str() {
+                "nfs" | "cifs" | "smb2" | "ceph" | "lustre" | "gpfs" | "fuse" => ", latency-prone",
+                "tmpfs" | "ramfs" => ", backed by RAM",
+                _ => "",
+            };
+
+            println!(
+                "\ton {} with {} blocks{}",
+                filesystem,
+                humanize(*block_size, config),
+                note
+            );
+        }
+
+        if let Some((extent, holes)) = self.sparse() {
+            println!(
+                "\tsparse: implied size {}, {} of it never written",
+                humanize(extent, config),
+                humanize(holes, config),
+            );
+        }
+
+        if config.verbose && self.prefetched != 0 {
+            println!(
+                "\tprefetched: {} via readahead",
+                humanize(self.prefetched, config)
+            );
+        }
+
+        if config.verbose && !self.advice.is_empty() {
+            println!("\tadvice: {}", self.advice.join(", "));
+        }
+
+        if config.verbose && !self.errors.is_empty() {
+            let errors: Vec<String> = self
+                .errors
+                .iter()
+                .map(|(errno, count)| format!("{} {}", errno, count))
+                .collect();
+
+            println!("\tfailed: {}", errors.join(", "));
+        }
+
+        if config.verbose && self.restarts != 0 {
+            println!("\trestarted {} times after a signal", self.restarts);
+        }
+
+        if config.verbose && self.nonblocking {
+            println!("\tnonblocking");
+        }
+
+        if config.verbose && self.eof != 0 {
+            println!("\treached EOF {} times", self.eof);
+        }
+
+        if config.verbose {
+            if let Some(lifetime) = self.lifetime() {
+                println!("\topen {:.1}s", lifetime);
+            }
+
+            // lazy opens and slow connection setups
+            if let Some(latency) = self.first_io_latency() {
+                let since = if self.connected.is_some() {
+                    "connect"
+                } else {
+                    "open"
+                };
+                println!("\tfirst I/O {:.3}s after {}", latency, since);
+            }
+        }
+
+        if config.verbose {
+            if let (Some(first_op), Some(last_op)) = (self.first_op, self.last_op) {
+                println!("\tsyscalls: first op #{}, last op #{}", first_op, last_op);
+            }
+        }
+    }
+}
+
+fn share(bytes: u64, total: u64, config: &Config) -> String {
+    if config.percent && total != 0 {
+        format!("\t{:.1}% of total", bytes as f64 * 100.0 / total as f64)
+    } else {
+        String::new()
+    }
+}
+
+// min, ops-weighted average and max of the requested op sizes
+fn op_sizes(freq: &HashMap<u64, u64>) -> Option<(u64, u64, u64)> {
+    let min = freq.keys().min()?;
+    let max = freq.keys().max()?;
+
+    let ops: u64 = freq.values().sum();
+    let total: u64 = freq.iter().map(|(op_size, n)| op_size * n).sum();
+
+    Some((*min, total / ops, *max))
+}
+
+fn freq_to_json(freq: &HashMap<u64, u64>) -> String {
+    let mut op_sizes: Vec<(&u64, &u64)> = freq.iter().collect();
+    op_sizes.sort();
+
+    let fields: Vec<String> = op_sizes
+        .iter()
+        .map(|(op_size, ops)| format!("\"{}\":{}", op_size, ops))
+        .collect();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+fn freq_from_json(value: Option<&Value>) -> Option<HashMap<u64, u64>> {
+    let mut freq = HashMap::new();
+    for (op_size, ops) in value?.as_object()? {
+        freq.insert(op_size.parse().ok()?, ops.as_u64()?);
     }
+    Some(freq)
 }
 
-fn humanize(bytes: u64) -> String {
-    ByteSize(bytes)
-        .to_string_as(true)
-        .replace("iB", "")
-        .replace(" ", "")
-        .to_uppercase()
+fn counts_to_json(counts: &BTreeMap<String, u64>) -> String {
+    let fields: Vec<String> = counts
+        .iter()
+        .map(|(name, count)| format!("{}:{}", json::escape(name), count))
+        .collect();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+fn option_to_json<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::from("null"), |value| value.to_string())
+}
+
+// FNV-1a, spelled out so the tokens stay the same across builds and Rust
+// releases, unlike those of the std hashers
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// the token for a single name, keeping its extension
+fn token(name: &str) -> String {
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot != 0 => name.split_at(dot),
+        _ => (name, ""),
+    };
+
+    format!("{:016x}{}", fnv1a(stem.as_bytes()), extension)
+}
+
+/// The deepest directory all absolute `paths` are in, `None` if that is the
+/// root or none of them are absolute. Relative paths and names like STDOUT
+/// are ignored.
+///
+/// ```
+/// use strace_analyzer::summary::common_directory;
+///
+/// let paths = vec![
+///     "/srv/builds/project/target/release/deps/a.rlib",
+///     "/srv/builds/project/target/release/deps/b.rlib",
+///     "/srv/builds/project/target/release/build/out.txt",
+///     "STDOUT",
+/// ];
+///
+/// assert_eq!(
+///     common_directory(paths).unwrap(),
+///     "/srv/builds/project/target/release"
+/// );
+/// assert_eq!(common_directory(vec!["/etc/hosts", "/srv/data"]), None);
+/// ```
+pub fn common_directory<'a, I>(paths: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut common: Option<Vec<&str>> = None;
+
+    for path in paths.into_iter().filter(|path| path.starts_with('/')) {
+        let components: Vec<&str> = path.split('/').collect();
+        let directory = &components[..components.len() - 1];
+
+        common = Some(match common {
+            None => directory.to_vec(),
+            Some(common) => common
+                .into_iter()
+                .zip(directory)
+                .take_while(|(a, b)| a == *b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+
+    let common = common?.join("/");
+    if common.is_empty() {
+        None
+    } else {
+        Some(common)
+    }
+}
+
+/// Whether `path` is a terminal: the controlling one, a virtual console, a
+/// pseudo-terminal or the console.
+pub fn is_tty(path: &str) -> bool {
+    lazy_static! {
+        static ref RE_TTY: Regex = Regex::new(r#"^/dev/(?:tty\d*|pts/\d+|ptmx|console)$"#).unwrap();
+    }
+
+    RE_TTY.is_match(path)
+}
+
+/// Trailing suffixes that tell rotated files apart: a separator followed by a
+/// number or an ISO date, optionally compressed, like `.1`, `-20240101` or
+/// `.2024-01-01.gz`. Override it with `--collapse-pattern`.
+pub const COLLAPSE_PATTERN: &str = r"[._-](?:\d{4}-\d{2}-\d{2}|\d+)(?:\.(?:gz|bz2|xz|zst))?$";
+
+/// Returns the logical name of a rotated file, replacing the first match of
+/// `pattern` with `*` but keeping a leading `.`, `-` or `_` separator, or
+/// `None` if the path does not match.
+///
+/// ```
+/// extern crate regex;
+/// extern crate strace_analyzer;
+///
+/// use regex::Regex;
+/// use strace_analyzer::summary::{collapse, COLLAPSE_PATTERN};
+///
+/// # fn main() {
+/// let pattern = Regex::new(COLLAPSE_PATTERN).unwrap();
+///
+/// for path in &["/var/log/app.log.1", "/var/log/app.log.2", "/var/log/app.log.3.gz"] {
+///     assert_eq!(collapse(path, &pattern).unwrap(), "/var/log/app.log.*");
+/// }
+///
+/// assert_eq!(collapse("/var/log/app.log", &pattern), None);
+/// # }
+/// ```
+pub fn collapse(path: &str, pattern: &Regex) -> Option<String> {
+    let suffix = pattern.find(path)?;
+    let separator = match path[suffix.start()..].chars().next() {
+        Some(c) if c == '.' || c == '-' || c == '_' => c.to_string(),
+        _ => String::new(),
+    };
+
+    Some(format!(
+        "{}{}*{}",
+        &path[..suffix.start()],
+        separator,
+        &path[suffix.end()..]
+    ))
+}
+
+/// Replaces every path component with a stable token, keeping the directory
+/// structure and file extensions intact. The tokens are the 64-bit FNV-1a
+/// hashes of the components, the same for every run and build.
+///
+/// ```
+/// use strace_analyzer::summary::anonymize;
+///
+/// assert_eq!(anonymize("/home/alice/notes.txt"), anonymize("/home/alice/notes.txt"));
+/// assert_ne!(anonymize("/home/alice/notes.txt"), anonymize("/home/bob/notes.txt"));
+/// assert_eq!(
+///     anonymize("/home/alice/notes.txt"),
+///     "/402d1bcc7e6f9d6e/508b2abb65a03907/40906c71b95bdbaa.txt",
+/// );
+/// assert_eq!(anonymize("notes.txt"), "40906c71b95bdbaa.txt");
+/// assert_eq!(anonymize("STDOUT"), "STDOUT");
+/// ```
+pub fn anonymize(path: &str) -> String {
+    // the standard streams are labels, not paths, and reveal nothing
+    if path == "STDIN" || path == "STDOUT" || path == "STDERR" {
+        return String::from(path);
+    }
+
+    let components: Vec<String> = path
+        .split('/')
+        .map(|component| {
+            if component.is_empty() || component == "." || component == ".." {
+                String::from(component)
+            } else {
+                token(component)
+            }
+        })
+        .collect();
+
+    components.join("/")
+}
+
+// the paths of unix sockets, abstract ones included, in a socket address
+fn anonymize_socket(address: &str) -> String {
+    lazy_static! {
+        static ref RE_SUN_PATH: Regex = Regex::new(r#"sun_path=(@?)"([^"]*)""#).unwrap();
+    }
+
+    RE_SUN_PATH
+        .replace_all(address, |cap: &Captures| {
+            format!("sun_path={}\"{}\"", &cap[1], anonymize(&cap[2]))
+        })
+        .into_owned()
+}
+
+// a small table of well-known ports, /etc/services differs between hosts
+const SERVICES: &[(u16, &str)] = &[
+    (21, "ftp"),
+    (22, "ssh"),
+    (25, "smtp"),
+    (53, "domain"),
+    (80, "http"),
+    (110, "pop3"),
+    (123, "ntp"),
+    (143, "imap"),
+    (389, "ldap"),
+    (443, "https"),
+    (465, "submissions"),
+    (587, "submission"),
+    (636, "ldaps"),
+    (993, "imaps"),
+    (995, "pop3s"),
+    (2049, "nfs"),
+    (3306, "mysql"),
+    (5432, "postgresql"),
+    (5672, "amqp"),
+    (6379, "redis"),
+    (8080, "http-alt"),
+    (9092, "kafka"),
+    (11211, "memcache"),
+    (27017, "mongodb"),
+];
+
+/// Replaces well-known port numbers in socket addresses with service names.
+///
+/// ```
+/// use strace_analyzer::summary::resolve_ports;
+///
+/// assert_eq!(resolve_ports("sin_port=htons(443)"), "sin_port=htons(https)");
+/// assert_eq!(resolve_ports("sin_port=htons(39150)"), "sin_port=htons(39150)");
+/// ```
+pub fn resolve_ports(address: &str) -> String {
+    lazy_static! {
+        static ref RE_PORT: Regex = Regex::new(r#"htons\((\d+)\)"#).unwrap();
+    }
+
+    RE_PORT
+        .replace_all(address, |cap: &Captures| {
+            let service = cap[1]
+                .parse::<u16>()
+                .ok()
+                .and_then(|port| SERVICES.iter().find(|(p, _)| *p == port));
+
+            match service {
+                Some((_, name)) => format!("htons({})", name),
+                None => String::from(&cap[0]),
+            }
+        })
+        .into_owned()
+}
+
+/// A path as shown to the user, anonymized with `--anonymize`.
+pub fn render_path(path: &str, config: &Config) -> String {
+    if config.anonymize {
+        anonymize(path)
+    } else {
+        String::from(path)
+    }
+}
+
+/// Renders a byte count in binary units, picking the unit by magnitude
+/// unless `--bytes-unit` fixes it. `--compact-sizes` renders them like
+/// `compact_size`.
+///
+/// ```
+/// use strace_analyzer::config::{Config, Unit};
+/// use strace_analyzer::summary::humanize;
+///
+/// assert_eq!(humanize(4096, &Config::default()), "4.0K");
+///
+/// let unit = |unit| Config { bytes_unit: Some(unit), ..Config::default() };
+/// assert_eq!(humanize(4096, &unit(Unit::B)), "4096B");
+/// assert_eq!(humanize(4096, &unit(Unit::K)), "4.000K");
+/// assert_eq!(humanize(4096, &unit(Unit::M)), "0.004M");
+/// assert_eq!(humanize(4096, &unit(Unit::G)), "0.000G");
+///
+/// let precise = Config { precision: 6, ..unit(Unit::G) };
+/// assert_eq!(humanize(4096, &precise), "0.000004G");
+/// ```
+pub fn humanize(bytes: u64, config: &Config) -> String {
+    match config.bytes_unit {
+        None if config.compact_sizes => compact_size(bytes),
+        Some(Unit::B) => format!("{}B", bytes),
+        Some(unit) => {
+            let (divisor, suffix) = match unit {
+                Unit::K => (1u64 << 10, "K"),
+                Unit::M => (1u64 << 20, "M"),
+                _ => (1u64 << 30, "G"),
+            };
+
+            format!(
+                "{:.*}{}",
+                config.precision,
+                bytes as f64 / divisor as f64,
+                suffix
+            )
+        }
+        None => ByteSize(bytes)
+            .to_string_as(true)
+            .replace("iB", "")
+            .replace(" ", "")
+            .to_uppercase(),
+    }
+}
+
+// the f_type magics of statfs, by the names strace decodes them to
+const FILESYSTEMS: &[(u64, &str, &str)] = &[
+    (0x0000_9fa0, "PROC_SUPER_MAGIC", "proc"),
+    (0x0000_6969, "NFS_SUPER_MAGIC", "nfs"),
+    // ext2 and ext3 share the magic of ext4
+    (0x0000_ef53, "EXT2_SUPER_MAGIC", "ext4"),
+    (0x00c3_6400, "CEPH_SUPER_MAGIC", "ceph"),
+    (0x0102_1994, "TMPFS_MAGIC", "tmpfs"),
+    (0x0bd0_0bd0, "LL_SUPER_MAGIC", "lustre"),
+    (0x4750_4653, "GPFS_SUPER_MAGIC", "gpfs"),
+    (0x5846_5342, "XFS_SUPER_MAGIC", "xfs"),
+    (0x6265_6572, "SYSFS_MAGIC", "sysfs"),
+    (0x6573_5546, "FUSE_SUPER_MAGIC", "fuse"),
+    (0x794c_7630, "OVERLAYFS_SUPER_MAGIC", "overlay"),
+    (0x8584_58f6, "RAMFS_MAGIC", "ramfs"),
+    (0x9123_683e, "BTRFS_SUPER_MAGIC", "btrfs"),
+    (0xfe53_4d42, "SMB2_MAGIC_NUMBER", "smb2"),
+    (0xff53_4d42, "CIFS_MAGIC_NUMBER", "cifs"),
+];
+
+/// The name of the filesystem with the `f_type` statfs reports, either
+/// decoded by strace or as a number with `-X raw`. Unknown types are kept as
+/// they are.
+///
+/// ```
+/// use strace_analyzer::summary::filesystem;
+///
+/// assert_eq!(filesystem("NFS_SUPER_MAGIC"), "nfs");
+/// assert_eq!(filesystem("0x1021994"), "tmpfs");
+/// assert_eq!(filesystem("61267"), "ext4");
+/// assert_eq!(filesystem("0x12345678"), "0x12345678");
+/// ```
+pub fn filesystem(f_type: &str) -> String {
+    let magic = match f_type.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => f_type.parse().ok(),
+    };
+
+    FILESYSTEMS
+        .iter()
+        .find(|(number, name, _)| Some(*number) == magic || *name == f_type)
+        .map_or(String::from(f_type), |(_, _, filesystem)| {
+            String::from(*filesystem)
+        })
+}
+
+/// Shortens `path` to at most `width` columns by replacing its middle with
+/// `...`, so that both the leading directories and the file name stay visible.
+///
+/// ```
+/// use strace_analyzer::summary::truncate;
+///
+/// let path = "/very/long/path/to/some/deeply/nested/file.log";
+/// assert_eq!(truncate(path, 24), "/very/long...ed/file.log");
+/// assert_eq!(truncate(path, 24).chars().count(), 24);
+/// assert_eq!(truncate(path, path.len()), path);
+/// assert_eq!(truncate("/data", 24), "/data");
+/// assert_eq!(truncate("/äöü/äöü/äöü", 7), "/ä...öü");
+/// assert_eq!(truncate(path, 2), "..");
+/// ```
+pub fn truncate(path: &str, width: usize) -> String {
+    const ELLIPSIS: &str = "...";
+
+    let chars: Vec<char> = path.chars().collect();
+    if chars.len() <= width {
+        return String::from(path);
+    }
+
+    if width <= ELLIPSIS.len() {
+        return ELLIPSIS[..width].to_string();
+    }
+
+    let head = (width - ELLIPSIS.len()) / 2;
+    let tail = width - ELLIPSIS.len() - head;
+
+    let mut truncated: String = chars[..head].iter().collect();
+    truncated.push_str(ELLIPSIS);
+    truncated.extend(&chars[chars.len() - tail..]);
+    truncated
+}
+
+/// A horizontal bar of `count` relative to `max`, which fills all of `width`
+/// columns, in eighths of a column. Any count above zero is visible.
+///
+/// ```
+/// use strace_analyzer::summary::bar;
+///
+/// assert_eq!(bar(8, 8, 10).chars().count(), 10);
+/// assert_eq!(bar(4, 8, 10), "█████");
+/// assert_eq!(bar(1, 8, 10), "█▎");
+/// assert_eq!(bar(1, 1000, 10), "▏");
+/// assert_eq!(bar(0, 8, 10), "");
+///
+/// // wider bars for larger counts
+/// let widths: Vec<usize> = [1, 2, 4, 8].iter().map(|n| bar(*n, 8, 40).chars().count()).collect();
+/// assert_eq!(widths, [5, 10, 20, 40]);
+/// ```
+pub fn bar(count: u64, max: u64, width: usize) -> String {
+    const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+
+    let eighths = (count as u128 * width as u128 * 8 / max as u128).max(1) as usize;
+
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(EIGHTHS[eighths % 8]);
+    }
+    bar
+}
+
+/// Prints the `rows` of a histogram, each a label and its count, `indent`
+/// tabs deep. With bars, each row ends with the bar of its count relative to
+/// the largest one, the whole line within `columns`.
+pub fn show_histogram(rows: &[(String, u64)], indent: usize, config: &Config) {
+    let tabs = "\t".repeat(indent);
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    // the tabs, the widest row and a space come before the bars
+    let label = rows
+        .iter()
+        .map(|(row, _)| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = config.columns.saturating_sub(8 * indent + label + 1);
+
+    for (row, count) in rows {
+        let bar = bar(*count, max, width);
+
+        if !config.bars || width == 0 {
+            println!("{}{}", tabs, row);
+        } else if config.color {
+            println!("{}{:<3$} \x1b[36m{}\x1b[0m", tabs, row, bar, label);
+        } else {
+            println!("{}{:<3$} {}", tabs, row, bar, label);
+        }
+    }
+}
+
+/// Renders a byte count for narrow terminals, in binary units with a single
+/// letter suffix and as few digits as possible: a decimal only below 10 of
+/// the unit and only if it isn't zero.
+///
+/// ```
+/// use strace_analyzer::summary::compact_size;
+///
+/// assert_eq!(compact_size(900), "900B");
+/// assert_eq!(compact_size(4096), "4K");
+/// assert_eq!(compact_size(1536), "1.5K");
+/// assert_eq!(compact_size(65536), "64K");
+/// assert_eq!(compact_size(1258291), "1.2M");
+/// assert_eq!(compact_size(300 << 20), "300M");
+/// assert_eq!(compact_size(5 << 30), "5G");
+///
+/// // no 1024 of a unit, that's one of the next
+/// assert_eq!(compact_size(1023 * 1024 + 400), "1023K");
+/// assert_eq!(compact_size(1024 * 1024 - 100), "1M");
+/// assert_eq!(compact_size((1 << 30) - (1 << 10)), "1G");
+/// assert_eq!(compact_size(10239), "10K");
+/// ```
+pub fn compact_size(bytes: u64) -> String {
+    let units = ["K", "M", "G", "T", "P"];
+
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    loop {
+        let tenths = (value * 10.0).round() / 10.0;
+        let rounded = if tenths < 10.0 { tenths } else { value.round() };
+
+        // the unit is the one of the value as printed, 1023.9K is 1M
+        if rounded >= 1024.0 && unit + 1 < units.len() {
+            value /= 1024.0;
+            unit += 1;
+            continue;
+        }
+
+        return if rounded.fract() != 0.0 {
+            format!("{:.1}{}", rounded, units[unit])
+        } else {
+            format!("{:.0}{}", rounded, units[unit])
+        };
+    }
 }
@@ -0,0 +1,78 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::Traces;
+use strace_analyzer::reader;
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
+
+const TRACE: &str = r#"
+    open("/srv/data", O_RDONLY) = 3
+    read(3, "..."..., 4096) = 4096
+    read(3, "..."..., 4096) = 100
+    read(3, "", 4096) = 0
+    close(3) = 0
+    open("/srv/out", O_WRONLY|O_CREAT, 0644) = 3
+    write(3, "café"..., 5) = 5
+    close(3) = 0
+"#;
+
+fn lines(input: &Path, chunks: &[Range<u64>]) -> Vec<String> {
+    chunks
+        .iter()
+        .flat_map(|chunk| {
+            let reader = BufReader::new(reader::chunk(input, chunk).unwrap());
+            reader.lines().map(Result::unwrap).collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+#[test]
+fn splits_mid_line_are_realigned_to_the_next_line() {
+    let traces = Traces::new();
+    let input = traces.file("trace.strace", TRACE);
+
+    let whole = lines(&input, &reader::split(&input, &[]).unwrap());
+    assert_eq!(whole.len(), 8);
+
+    let len = input.metadata().unwrap().len();
+    let second = whole[0].len() as u64 + 1;
+
+    // in the middle of the first line, right at the start of the second, twice
+    // in the same line, in the middle of the multi-byte character and at the end
+    let unicode = fs::read(&input)
+        .unwrap()
+        .iter()
+        .position(|b| *b == 0xa9)
+        .unwrap() as u64;
+    let offsets = [10, second, second + 5, second + 7, unicode, len];
+
+    let chunks = reader::split(&input, &offsets).unwrap();
+    assert_eq!(chunks[0], 0..second);
+    assert_eq!(chunks[1].start, second);
+    assert_eq!(chunks.last().unwrap().end, len);
+
+    for pair in chunks.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+    }
+
+    assert_eq!(lines(&input, &chunks), whole);
+}
+
+#[test]
+fn chunks_count_the_same_lines_as_a_single_pass() {
+    let traces = Traces::new();
+    let input = traces.file("trace.strace", &TRACE.repeat(50));
+
+    let whole = lines(&input, &reader::chunks(&input, 1).unwrap());
+
+    for jobs in 2..9 {
+        let chunks = reader::chunks(&input, jobs).unwrap();
+        assert_eq!(chunks.len(), jobs);
+        assert_eq!(lines(&input, &chunks), whole);
+    }
+}
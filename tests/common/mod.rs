@@ -0,0 +1,181 @@
+// shared by the integration tests, each of which uses only some of it
+#![allow(dead_code)]
+
+use strace_analyzer::analysis::{analyze, analyze_with, standard_fds};
+use strace_analyzer::config::Config;
+use strace_analyzer::report::Report;
+use strace_analyzer::summary::Summary;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TRACES: AtomicUsize = AtomicUsize::new(0);
+
+/// A scratch directory for trace files, removed again when dropped.
+pub struct Traces {
+    pub dir: PathBuf,
+}
+
+impl Traces {
+    pub fn new() -> Traces {
+        let dir = env::temp_dir().join(format!(
+            "strace-analyzer-test-{}-{}",
+            std::process::id(),
+            TRACES.fetch_add(1, Ordering::SeqCst),
+        ));
+
+        fs::create_dir_all(&dir).unwrap();
+
+        Traces { dir }
+    }
+
+    /// Writes `content` to `name`, e.g. `app.strace` or `app.strace.4242` for
+    /// the trace of a child with `-ff`.
+    pub fn file(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.dir.join(name);
+        fs::write(&path, unindent(content)).unwrap();
+        path
+    }
+
+    /// Runs the binary from within the scratch directory, with it as home,
+    /// too, so no one's `.strace-analyzer.toml` gets in the way.
+    pub fn run(&self, args: &[&str]) -> String {
+        self.run_with_stderr(args).0
+    }
+
+    /// Runs the binary like `run`, returning its stdout and stderr.
+    pub fn run_with_stderr(&self, args: &[&str]) -> (String, String) {
+        let output = self.command(args);
+
+        assert!(
+            output.status.success(),
+            "strace-analyzer {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr),
+        );
+
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    }
+
+    /// Runs the binary like `run` on a terminal `columns` wide.
+    pub fn run_with_columns(&self, columns: usize, args: &[&str]) -> String {
+        let output = self
+            .command_with_env(args, &[("COLUMNS", &columns.to_string())])
+            .stdout;
+
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Runs the binary like `run`, expecting it to fail, and returns its
+    /// stderr.
+    pub fn fail(&self, args: &[&str]) -> String {
+        let output = self.command(args);
+
+        assert!(
+            !output.status.success(),
+            "strace-analyzer {:?} succeeded",
+            args
+        );
+
+        String::from_utf8(output.stderr).unwrap()
+    }
+
+    fn command(&self, args: &[&str]) -> Output {
+        self.command_with_env(args, &[])
+    }
+
+    fn command_with_env(&self, args: &[&str], vars: &[(&str, &str)]) -> Output {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_strace-analyzer"));
+        command
+            .args(args)
+            .current_dir(&self.dir)
+            .env("HOME", &self.dir)
+            .env_remove("COLUMNS");
+
+        for (name, value) in vars {
+            command.env(name, value);
+        }
+
+        command.output().unwrap()
+    }
+}
+
+impl Drop for Traces {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+// lets the traces be indented like the test code around them
+fn unindent(content: &str) -> String {
+    content
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+pub fn analyze_str(trace: &str) -> Report<'static> {
+    analyze_str_with(trace, &Config::default())
+}
+
+pub fn analyze_str_with(trace: &str, config: &Config) -> Report<'static> {
+    let traces = Traces::new();
+    analyze_path(&traces.file("trace.strace", trace), config)
+}
+
+pub fn analyze_path(input: &Path, config: &Config) -> Report<'static> {
+    let mut report = Report::new();
+    analyze(&mut standard_fds(), input, &mut report, config).unwrap();
+    report
+}
+
+/// The finalized summaries in the order they were pushed to the report,
+/// including the standard descriptors still open at the end of the trace.
+pub fn summaries(trace: &str) -> Vec<Summary> {
+    summaries_with(trace, &Config::default())
+}
+
+pub fn summaries_with(trace: &str, config: &Config) -> Vec<Summary> {
+    let traces = Traces::new();
+    let input = traces.file("trace.strace", trace);
+
+    let mut summaries = Vec::new();
+    analyze_with(&input, config, |summary| summaries.push(summary)).unwrap();
+    summaries
+}
+
+/// The summary of the file at `path`, merged if it was opened several times.
+pub fn file(summaries: &[Summary], path: &str) -> Summary {
+    let mut matching = summaries
+        .iter()
+        .filter(|summary| summary.field("path", &Config::default()).as_str() == Some(path));
+
+    let mut merged = matching
+        .next()
+        .unwrap_or_else(|| panic!("no summary of {}", path))
+        .clone();
+
+    for summary in matching {
+        merged.merge(summary);
+    }
+
+    merged
+}
+
+/// Runs the binary on `trace` with the `args` ahead of the trace file.
+pub fn output(args: &[&str], trace: &str) -> String {
+    let traces = Traces::new();
+    traces.file("trace.strace", trace);
+
+    let mut args = args.to_vec();
+    args.push("trace.strace");
+    traces.run(&args)
+}
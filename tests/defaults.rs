@@ -0,0 +1,58 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::Traces;
+
+#[test]
+fn defaults_file_sets_options_with_values() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 100) = 100
+        close(3) = 0
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3
+        connect(3, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+        write(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+    traces.file(
+        ".strace-analyzer.toml",
+        r#"
+        only = ["files"]
+        ratio = true
+        "#,
+    );
+
+    let output = traces.run(&["trace.strace"]);
+
+    assert!(output.contains("FILE:/srv/data\n"), "{}", output);
+    assert!(!output.contains("SOCKET"), "{}", output);
+    assert!(output.contains("\nREAD:WRITE RATIOS:\n"), "{}", output);
+}
+
+#[test]
+fn command_line_overrides_defaults_file() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 100) = 100
+        close(3) = 0
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3
+        connect(3, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+        write(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+    traces.file(".strace-analyzer.toml", "only = [\"files\"]\n");
+
+    let output = traces.run(&["--only", "sockets", "trace.strace"]);
+
+    assert!(!output.contains("FILE:/srv/data"), "{}", output);
+    assert!(output.contains("SOCKET"), "{}", output);
+}
@@ -0,0 +1,166 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use common::{analyze_path, file, Traces};
+use strace_analyzer::analysis::{analyze, standard_fds};
+use strace_analyzer::config::Config;
+use strace_analyzer::events;
+use strace_analyzer::report::Report;
+
+#[test]
+fn strict_fails_on_a_malformed_syscall_line() {
+    let traces = Traces::new();
+    let input = traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        hello from the program
+        read(3, "..."..., 100 = 100
+        close(3) = 0
+        "#,
+    );
+
+    let mut report = Report::new();
+    analyze(&mut standard_fds(), &input, &mut report, &Config::default()).unwrap();
+
+    let config = Config {
+        strict: true,
+        ..Config::default()
+    };
+    let mut report = Report::new();
+    let error = analyze(&mut standard_fds(), &input, &mut report, &config).unwrap_err();
+
+    assert!(
+        error
+            .to_string()
+            .ends_with("trace.strace:3: unparseable syscall: read(3, \"...\"..., 100 = 100"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn follow_reads_a_fifo_until_the_program_exits() {
+    let traces = Traces::new();
+    let fifo = traces.dir.join("trace.fifo");
+    assert!(Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .unwrap()
+        .success());
+
+    // a live strace pausing and a writer reconnecting don't end the analysis
+    let path = fifo.clone();
+    let writer = thread::spawn(move || {
+        let mut strace = OpenOptions::new().write(true).open(&path).unwrap();
+        writeln!(strace, "open(\"/srv/data\", O_RDONLY) = 3").unwrap();
+        thread::sleep(Duration::from_millis(200));
+        writeln!(strace, "read(3, \"...\"..., 4096) = 4096").unwrap();
+        drop(strace);
+
+        let mut strace = OpenOptions::new().write(true).open(&path).unwrap();
+        writeln!(strace, "read(3, \"...\"..., 4096) = 4096").unwrap();
+        writeln!(strace, "+++ exited with 0 +++").unwrap();
+    });
+
+    let config = Config {
+        follow: true,
+        ..Config::default()
+    };
+    let report = analyze_path(&fifo, &config);
+    writer.join().unwrap();
+
+    assert_eq!(report.merged_by_path()["/srv/data"].read_bytes(), 8192);
+}
+
+#[test]
+fn json_events_keep_an_fd_table_per_pid() {
+    let traces = Traces::new();
+    let input = traces.file(
+        "trace.jsonl",
+        r#"
+        {"pid": 7, "fd": 3, "op": "open", "path": "/srv/data"}
+        {"pid": 8, "fd": 3, "op": "open", "path": "/srv/log"}
+        {"pid": 7, "fd": 3, "op": "read", "bytes": 100, "size": 4096, "offset": 8192}
+        {"pid": 8, "fd": 3, "op": "write", "bytes": 512}
+        {"pid": 9, "fd": 4, "op": "write", "bytes": 64, "path": "/srv/out"}
+        {"pid": 7, "fd": 3, "op": "close"}
+        "#,
+    );
+
+    let mut summaries = Vec::new();
+    {
+        let mut report = Report::with_sink(|summary| summaries.push(summary));
+        events::analyze(&input, &mut report, &Config::default()).unwrap();
+    }
+
+    let data = file(&summaries, "/srv/data");
+    assert_eq!(
+        (data.read_bytes(), data.read_ops(), data.write_bytes()),
+        (100, 1, 0)
+    );
+
+    let log = file(&summaries, "/srv/log");
+    assert_eq!(
+        (log.write_bytes(), log.write_ops(), log.read_bytes()),
+        (512, 1, 0)
+    );
+
+    // an fd first seen in a write is named by the event's path
+    assert_eq!(file(&summaries, "/srv/out").write_bytes(), 64);
+}
+
+#[test]
+fn json_events_input_format_on_the_command_line() {
+    let traces = Traces::new();
+    traces.file(
+        "app.jsonl",
+        r#"
+        {"pid": 7, "fd": 3, "op": "open", "path": "/srv/data"}
+        {"pid": 7, "fd": 3, "op": "read", "bytes": 4096}
+        not json
+        {"pid": 7, "fd": 3, "op": "close"}
+        "#,
+    );
+
+    let output = traces.run(&["--input-format", "json-events", "--lenient", "app.jsonl"]);
+
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 4.0K with 1 ops (4.0K / op)\n"),
+        "{}",
+        output
+    );
+    assert!(output.contains("skipped 1 lines"), "{}", output);
+}
+
+#[test]
+fn json_events_start_each_process_with_its_standard_streams() {
+    let traces = Traces::new();
+    let input = traces.file(
+        "trace.jsonl",
+        r#"
+        {"pid": 7, "fd": 3, "op": "open", "path": "/srv/data"}
+        {"pid": 7, "fd": 3, "op": "read", "bytes": 4096}
+        {"pid": 7, "fd": 3, "op": "read", "bytes": 100, "size": 4096}
+        {"pid": 7, "fd": 3, "op": "close"}
+        {"pid": 7, "fd": 1, "op": "write", "bytes": 10}
+        {"pid": 8, "fd": 1, "op": "write", "bytes": 5}
+        "#,
+    );
+
+    let mut report = Report::new();
+    events::analyze(&input, &mut report, &Config::default()).unwrap();
+
+    let merged = report.merged_by_path();
+    assert_eq!(merged["/srv/data"].read_bytes(), 4196);
+    assert_eq!(merged["/srv/data"].read_ops(), 2);
+    assert_eq!(merged["STDOUT"].write_bytes(), 15);
+    assert_eq!(merged["STDOUT"].write_ops(), 2);
+}
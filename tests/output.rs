@@ -0,0 +1,1736 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::{output, Traces};
+
+#[test]
+fn first_and_last_op_are_sequence_numbers_of_the_io() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        open("/srv/other", O_RDONLY) = 4
+        read(3, "..."..., 10) = 10
+        read(4, "..."..., 10) = 10
+        read(3, "..."..., 10) = 10
+        fstat(3, {st_mode=S_IFREG|0644, st_size=20, ...}) = 0
+        close(3) = 0
+        close(4) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 20B with 2 ops (10B / op)\n\taccess: 1 sequential / 0 random ops\n\tlargest op: read 10B, write 0B\n\tread coverage: 100.0% of 20B\n\tsyscalls: first op #3, last op #5\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("\tsyscalls: first op #4, last op #4\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn close_range_closes_the_fds_in_the_range() {
+    let trace = r#"
+        open("/srv/a", O_RDONLY) = 3
+        open("/srv/b", O_RDONLY) = 4
+        open("/srv/c", O_RDONLY) = 5
+        close_range(4, ~0U, 0) = 0
+        read(3, "..."..., 10) = 10
+        read(4, "..."..., 10) = 10
+        read(5, "..."..., 10) = 10
+        close(3) = 0
+        "#;
+
+    assert_eq!(
+        output(&[], trace),
+        "FILE:/srv/a\n\tread 10B with 1 ops (10B / op)\npeak open descriptors: 6\n"
+    );
+
+    let output = output(&["-v"], trace);
+    assert!(
+        output.starts_with("FILE:/srv/b\nFILE:/srv/c\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn close_range_cloexec_closes_the_fds_on_execve() {
+    let output = output(
+        &[],
+        r#"
+        open("/srv/a", O_RDONLY) = 3
+        open("/srv/b", O_RDONLY) = 4
+        close_range(4, 4, CLOSE_RANGE_CLOEXEC) = 0
+        read(4, "..."..., 10) = 10
+        execve("/usr/bin/true", ["true"], 0x7ffd /* 20 vars */) = 0
+        read(3, "..."..., 10) = 10
+        read(4, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/b\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         FILE:/srv/a\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         peak open descriptors: 5\n"
+    );
+}
+
+#[test]
+fn pid_overview_counts_fds_still_open_at_the_end() {
+    let output = output(
+        &["--pid-overview"],
+        r#"
+        open("/srv/log", O_RDONLY) = 3
+        read(3, "..."..., 1000) = 1000
+        open("/srv/data", O_RDONLY) = 4
+        read(4, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "PID:trace.strace\tread 5.0K with 2 ops\twrite 0B with 0 ops\tpeak 5 open fds\n"
+        ),
+        "{}",
+        output,
+    );
+}
+
+#[test]
+fn pid_overview_counts_fds_still_open_in_child_traces() {
+    let traces = Traces::new();
+    traces.file(
+        "app",
+        r#"
+        clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 200
+        open("/srv/parent", O_RDONLY) = 3
+        read(3, "..."..., 100) = 100
+        "#,
+    );
+    traces.file(
+        "app.200",
+        r#"
+        open("/srv/child", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        "#,
+    );
+
+    let output = traces.run(&["--pid-overview", "app"]);
+
+    assert!(
+        output.starts_with(
+            "PID:app\tread 100B with 1 ops\twrite 0B with 0 ops\tpeak 4 open fds\n\
+             PID:200\tread 10B with 1 ops\twrite 0B with 0 ops\tpeak 4 open fds\n"
+        ),
+        "{}",
+        output,
+    );
+}
+
+#[test]
+fn bare_pid_prefixes_keep_separate_fd_tables() {
+    let output = output(
+        &[],
+        r#"
+        100 open("/srv/a", O_RDONLY) = 3
+        200 open("/srv/b", O_RDONLY) = 3
+        100 read(3, "..."..., 100) = 100
+        200 read(3, "..."..., 10) = 10
+        100 close(3) = 0
+        200 close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/a\n\
+         \tread 100B with 1 ops (100B / op)\n\
+         FILE:/srv/b\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         peak open descriptors: 4\n"
+    );
+}
+
+#[test]
+fn bracketed_pid_prefixes_keep_separate_fd_tables() {
+    let output = output(
+        &[],
+        r#"
+        [pid 100] open("/srv/a", O_RDONLY) = 3
+        [pid 200] open("/srv/b", O_RDONLY) = 3
+        [pid  100] read(3, "..."..., 100) = 100
+        [pid 200] read(3, "..."..., 10) = 10
+        [pid 100] close(3) = 0
+        [pid 200] close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/a\n\
+         \tread 100B with 1 ops (100B / op)\n\
+         FILE:/srv/b\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         peak open descriptors: 4\n"
+    );
+}
+
+#[test]
+fn children_inherit_a_copy_of_the_fds() {
+    let output = output(
+        &[],
+        r#"
+        open("/srv/shared", O_RDONLY) = 3
+        clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 200
+        [pid 200] read(3, "..."..., 10) = 10
+        [pid 200] open("/srv/child", O_RDONLY) = 4
+        [pid 100] read(3, "..."..., 100) = 100
+        [pid 100] read(4, "..."..., 100) = 100
+        [pid 200] read(4, "..."..., 5) = 5
+        [pid 200] close(4) = 0
+        [pid 200] close(3) = 0
+        [pid 100] close(3) = 0
+        "#,
+    );
+
+    // the parent's fd 4 was never opened
+    assert_eq!(
+        output,
+        "FILE:/srv/child\n\
+         \tread 5B with 1 ops (5B / op)\n\
+         FILE:/srv/shared\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         FILE:/srv/shared\n\
+         \tread 100B with 1 ops (100B / op)\n\
+         peak open descriptors: 5\n"
+    );
+}
+
+#[test]
+fn clone_files_shares_the_fds() {
+    let output = output(
+        &[],
+        r#"
+        clone(child_stack=0x7f, flags=CLONE_VM|CLONE_FS|CLONE_FILES|SIGCHLD) = 200
+        [pid 200] open("/srv/child", O_RDONLY) = 3
+        [pid 100] read(3, "..."..., 100) = 100
+        [pid 200] read(3, "..."..., 5) = 5
+        [pid 100] close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with("FILE:/srv/child\n\tread 105B with 2 ops"),
+        "{}",
+        output,
+    );
+}
+
+#[test]
+fn children_seen_before_the_clone_returns_keep_their_fds() {
+    let output = output(
+        &[],
+        r#"
+        100 open("/srv/shared", O_RDONLY) = 3
+        200 open("/srv/child", O_RDONLY) = 4
+        100 clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 200
+        200 read(3, "..."..., 10) = 10
+        200 read(4, "..."..., 5) = 5
+        200 close(4) = 0
+        200 close(3) = 0
+        100 close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/child\n\
+         \tread 5B with 1 ops (5B / op)\n\
+         FILE:/srv/shared\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         peak open descriptors: 4\n"
+    );
+}
+
+#[test]
+fn pid_overview_splits_a_single_follow_trace_by_pid() {
+    let output = output(
+        &["--pid-overview"],
+        r#"
+        open("/srv/shared", O_RDONLY) = 3
+        clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 200
+        [pid 200] read(3, "..."..., 10) = 10
+        [pid 200] open("/srv/child", O_WRONLY) = 4
+        [pid 200] write(4, "..."..., 5) = 5
+        [pid 100] read(3, "..."..., 100) = 100
+        [pid 200] +++ exited with 0 +++
+        [pid 100] +++ exited with 1 +++
+        "#,
+    );
+
+    let lines: Vec<&str> = output.lines().take(2).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "PID:100\tread 100B with 1 ops\twrite 0B with 0 ops\tpeak 4 open fds\texited with 1",
+            "PID:200\tread 10B with 1 ops\twrite 5B with 1 ops\tpeak 5 open fds\texited with 0",
+        ],
+    );
+}
+
+#[test]
+fn pid_overview_attributes_threads_to_their_process() {
+    let output = output(
+        &["--pid-overview"],
+        r#"
+        100 open("/srv/data", O_RDONLY) = 3
+        100 clone(child_stack=0x7f, flags=CLONE_VM|CLONE_FS|CLONE_FILES|CLONE_SIGHAND|CLONE_THREAD|CLONE_SYSVSEM) = 101
+        101 read(3, "..."..., 10) = 10
+        100 read(3, "..."..., 100) = 100
+        101 +++ exited with 0 +++
+        100 +++ exited with 0 +++
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "PID:100\tread 110B with 2 ops\twrite 0B with 0 ops\tpeak 4 open fds\texited with 0\n"
+        ),
+        "{}",
+        output,
+    );
+    assert!(!output.contains("PID:101"), "{}", output);
+}
+
+#[test]
+fn ratio_combines_the_fds_of_each_file() {
+    let output = output(
+        &["--ratio"],
+        r#"
+        open("/srv/cache", O_RDONLY) = 3
+        read(3, "..."..., 900) = 900
+        close(3) = 0
+        open("/srv/cache", O_WRONLY) = 3
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        open("/srv/log", O_RDONLY) = 3
+        read(3, "..."..., 100) = 100
+        close(3) = 0
+        open("/srv/log", O_WRONLY) = 3
+        write(3, "..."..., 400) = 400
+        close(3) = 0
+        open("/srv/db", O_RDONLY) = 3
+        read(3, "..."..., 200) = 200
+        close(3) = 0
+        open("/srv/db", O_WRONLY) = 3
+        write(3, "..."..., 200) = 200
+        close(3) = 0
+        open("/srv/ro", O_RDONLY) = 3
+        read(3, "..."..., 300) = 300
+        close(3) = 0
+        open("/srv/wo", O_WRONLY) = 3
+        write(3, "..."..., 300) = 300
+        close(3) = 0
+        "#,
+    );
+
+    assert!(!output.contains("\tread:write ratio"), "{}", output);
+    assert!(
+        output.contains(
+            "\nREAD:WRITE RATIOS:\n\
+             \t/srv/cache: 9.00\n\
+             \t/srv/db: 1.00\n\
+             \t/srv/log: 0.25\n\
+             \t/srv/ro: ∞\n\
+             \t/srv/wo: 0\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn ltrace_streams_are_identified_by_their_pointer() {
+    let output = output(
+        &["--input-format", "ltrace"],
+        r#"
+        fopen("/srv/in", "r") = 0x55d0c0a012a0
+        fopen("/srv/out", "w") = 0x55d0c0a014e0
+        fread(0x7ffd2c3e1a00, 1, 4096, 0x55d0c0a012a0) = 4096
+        libapp.so->fread(0x7ffd2c3e1a00, 16, 10, 0x55d0c0a012a0) = 4
+        fwrite(0x7ffd2c3e1a00, 8, 8, 0x55d0c0a014e0) = 8
+        fclose(0x55d0c0a012a0) = 0
+        fclose(0x55d0c0a014e0) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/in\n\
+         \tread 4.1K with 2 ops (4.0K / op)\n\
+         FILE:/srv/out\n\
+         \twrite 64B with 1 ops (64B / op)\n"
+    );
+}
+
+#[test]
+fn zero_byte_reads_count_as_eof_not_as_reads() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 100
+        read(3, "", 4096) = 0
+        read(3, "", 4096) = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains(
+            "FILE:/srv/data\n\
+             \tread 4.1K with 2 ops (4.0K / op)\n\
+             \taccess: 3 sequential / 0 random ops\n\
+             \tlargest op: read 4.0K, write 0B\n\
+             \treached EOF 2 times\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn zero_size_reads_are_not_eof() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "", 0) = 0
+        read(3, "..."..., 4096) = 100
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 100B with 2 ops (4.0K / op)\n\taccess: 1 sequential / 0 random ops\n\tlargest op: read 100B, write 0B\n\tsyscalls:"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn diff_compares_per_file_io_against_the_baseline() {
+    let traces = Traces::new();
+    traces.file(
+        "new.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        open("/srv/new", O_WRONLY) = 3
+        write(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+    traces.file(
+        "old.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 1024) = 1024
+        close(3) = 0
+        open("/srv/gone", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        traces.run(&["--diff", "old.strace", "new.strace"]),
+        "FILE:/srv/data\n\
+         \tread +3.0K with +0 ops\n\
+         FILE:/srv/new\n\
+         \tonly in new.strace\n\
+         FILE:/srv/gone\n\
+         \tonly in old.strace\n",
+    );
+}
+
+#[test]
+fn getsockname_and_getpeername_fill_the_endpoints() {
+    let output = output(
+        &["-v"],
+        r#"
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3
+        connect(3, {sa_family=AF_INET, sin_port=htons(5432), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+        getsockname(3, {sa_family=AF_INET, sin_port=htons(40000), sin_addr=inet_addr("10.0.0.2")}, [16]) = 0
+        getpeername(3, {sa_family=AF_INET, sin_port=htons(9999), sin_addr=inet_addr("10.0.0.9")}, [16]) = 0
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 4
+        getpeername(4, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("10.0.0.3")}, [16]) = 0
+        close(3) = 0
+        close(4) = 0
+        "#,
+    );
+
+    // the connect already told where the first one went
+    assert!(
+        output.starts_with(
+            "SOCKET:\n\
+             \tbind: {sa_family=AF_INET, sin_port=htons(40000), sin_addr=inet_addr(\"10.0.0.2\")}\n\
+             \tconnect: {sa_family=AF_INET, sin_port=htons(5432), sin_addr=inet_addr(\"10.0.0.1\")}\n\
+             SOCKET:\n\
+             \tconnect: {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr(\"10.0.0.3\")}\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn unknown_syscalls_are_tallied() {
+    let output = output(
+        &["--unknown-syscalls"],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        frobnicate(3, 1) = 0
+        frobnicate(3, 2) = 0
+        kcmp(1, 2, KCMP_FILE, 3, 4) = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("\nUNKNOWN SYSCALLS:\n\tfrobnicate: 2\n\tkcmp: 1\n"),
+        "{}",
+        output,
+    );
+}
+
+#[test]
+fn decoded_io_uring_submissions_are_attributed_to_their_fds() {
+    let output = output(
+        &[],
+        r#"
+        io_uring_setup(8, {flags=0, sq_thread_cpu=0, sq_thread_idle=0}) = 4
+        open("/srv/in", O_RDONLY) = 3
+        open("/srv/out", O_WRONLY|O_CREAT, 0644) = 5
+        io_uring_enter(4, 3, 0, IORING_ENTER_GETEVENTS, [{opcode=IORING_OP_READ, fd=3, off=0, len=4096}, {opcode=IORING_OP_WRITEV, fd=5, off=0, addr=[{iov_base="...", iov_len=512}, {iov_base="...", iov_len=256}], len=2}, {opcode=IORING_OP_NOP, fd=-1}], 8) = 3
+        io_uring_enter(4</anon_inode:[io_uring]>, 2, 0, 0, [{opcode=IORING_OP_READ_FIXED, fd=3</srv/in>, off=4096, len=1024}, {opcode=IORING_OP_READ, fd=3, off=5120, len=1024}], 8) = 1
+        close(3) = 0
+        close(5) = 0
+        close(4) = 0
+        "#,
+    );
+
+    // the kernel consumed only the first entry of the second batch
+    assert!(
+        output.starts_with(
+            "FILE:/srv/in\n\
+             \tread 5.0K with 2 ops (4.0K / op)\n\
+             FILE:/srv/out\n\
+             \twrite 768B with 1 ops (768B / op)\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn undecoded_io_uring_submissions_only_count_the_ring() {
+    let output = output(
+        &[],
+        r#"
+        io_uring_setup(8, {flags=0, sq_thread_cpu=0, sq_thread_idle=0}) = 4
+        open("/srv/in", O_RDONLY) = 3
+        io_uring_enter(4, 2, 0, 0, NULL, 8) = 2
+        close(3) = 0
+        close(4) = 0
+        "#,
+    );
+
+    assert!(!output.contains("FILE:/srv/in"), "{}", output);
+    assert!(
+        output.contains("\tentered 1 times, submitted 2 entries\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn special_devices_are_labeled() {
+    let trace = r#"
+        open("/dev/null", O_WRONLY) = 3
+        write(3, "..."..., 100) = 100
+        open("/dev/zero", O_RDONLY) = 4
+        read(4, "..."..., 4096) = 4096
+        close(3) = 0
+        close(4) = 0
+        "#;
+
+    // /dev/null is hidden like the other system paths by default
+    assert_eq!(
+        output(&[], trace),
+        "FILE:/dev/zero\n\tread 4.0K with 1 ops (4.0K / op)\npeak open descriptors: 5\n",
+    );
+
+    assert_eq!(
+        output(&["--note-special-devices"], trace),
+        "FILE:/dev/null (discarded, not real I/O)\n\
+         \twrite 100B with 1 ops (100B / op)\n\
+         FILE:/dev/zero (synthetic, not real I/O)\n\
+         \tread 4.0K with 1 ops (4.0K / op)\n\
+         peak open descriptors: 5\n",
+    );
+}
+
+#[test]
+fn timeseries_buckets_the_bytes_by_interval() {
+    let output = output(
+        &["--timeseries", "1"],
+        r#"
+        1000.000000 open("/srv/data", O_RDWR) = 3
+        1000.100000 read(3, "..."..., 1000) = 1000
+        1000.900000 read(3, "..."..., 24) = 24
+        1001.200000 write(3, "..."..., 2048) = 2048
+        1003.500000 read(3, "..."..., 5) = 5
+        1003.600000 close(3) = 0
+        "#,
+    );
+
+    // idle intervals are there, too
+    assert_eq!(
+        output,
+        "time,read_bytes,write_bytes\n0,1024,0\n1,0,2048\n2,0,0\n3,5,0\n",
+    );
+}
+
+#[test]
+fn lines_longer_than_the_limit_are_skipped() {
+    let long = format!("read(3, \"{}\", 300) = 300", "x".repeat(300));
+    let trace = format!(
+        "open(\"/srv/data\", O_RDONLY) = 3\n{}\nread(3, \"...\"..., 10) = 10\nclose(3) = 0\n",
+        long,
+    );
+
+    assert_eq!(
+        output(&["--max-line-length", "100"], &trace),
+        "FILE:/srv/data\n\tread 10B with 1 ops (10B / op)\npeak open descriptors: 4\n"
+    );
+    assert_eq!(
+        output(&[], &trace),
+        "FILE:/srv/data\n\tread 310B with 2 ops (300B / op)\npeak open descriptors: 4\n"
+    );
+}
+
+#[test]
+fn only_keeps_the_selected_kinds() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 4
+        pipe([5, 6]) = 0
+        read(3, "..."..., 10) = 10
+        write(4, "..."..., 10) = 10
+        write(6, "..."..., 10) = 10
+        close(3) = 0
+        close(4) = 0
+        close(5) = 0
+        close(6) = 0
+        "#;
+
+    let kinds = |only: &str| -> Vec<String> {
+        output(&["-v", "--only", only], trace)
+            .lines()
+            .filter(|line| !line.starts_with('\t') && !line.starts_with("peak"))
+            .map(|line| line.split(':').next().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(kinds("sockets"), vec!["SOCKET"]);
+    assert_eq!(kinds("pipes"), vec!["PIPE", "PIPE"]);
+    // including the standard streams
+    assert_eq!(
+        kinds("files,sockets"),
+        vec!["FILE", "SOCKET", "FILE", "FILE", "FILE"]
+    );
+}
+
+#[test]
+fn peak_open_descriptors_end_the_output() {
+    let output = output(
+        &[],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        open("/srv/other", O_RDONLY) = 4
+        close(4) = 0
+        open("/srv/more", O_RDONLY) = 4
+        open("/srv/last", O_RDONLY) = 5
+        close(5) = 0
+        close(4) = 0
+        read(3, "..."..., 100) = 100
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.ends_with("\npeak open descriptors: 6\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn fcntl_setfl_tracks_nonblocking_transitions() {
+    let output = output(
+        &["-v"],
+        r#"
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3
+        fcntl(3, F_SETFL, O_RDWR|O_NONBLOCK) = 0
+        socket(AF_INET, SOCK_STREAM|SOCK_NONBLOCK, IPPROTO_TCP) = 4
+        fcntl(4, F_SETFL, O_RDWR) = 0
+        fcntl(3, F_DUPFD, 10) = 10
+        close(4) = 0
+        close(3) = 0
+        close(10) = 0
+        "#,
+    );
+
+    // closed in the order 4, 3 and the duplicate of 3
+    assert!(
+        output.starts_with("SOCKET:\nSOCKET:\n\tnonblocking\nSOCKET:\n\tnonblocking\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn quiet_suppresses_the_diagnostics() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/idle", O_RDONLY) = 3
+        close(3) = 0
+        close(7) = 0
+        "#,
+    );
+
+    let (_, stderr) = traces.run_with_stderr(&["--debug", "trace.strace"]);
+    assert!(stderr.contains("[debug] no I/O\n"), "{}", stderr);
+    assert!(stderr.contains("[close] unknown fd 7\n"), "{}", stderr);
+
+    let (stdout, stderr) = traces.run_with_stderr(&["--debug", "--quiet", "trace.strace"]);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout, "peak open descriptors: 4\n");
+}
+
+#[test]
+fn symlinked_devices_resolve_to_the_fds_they_point_to() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/dev/stdin", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        open("/srv/data", O_RDONLY) = 4
+        openat(AT_FDCWD, "/proc/self/fd/4", O_RDONLY) = 5
+        open("/dev/fd/4", O_RDONLY) = 6
+        read(5, "..."..., 100) = 100
+        read(6, "..."..., 1000) = 1000
+        open("/dev/fd/9", O_RDONLY) = 7
+        close(3) = 0
+        close(5) = 0
+        close(6) = 0
+        close(4) = 0
+        close(7) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "FILE:STDIN\n\
+             \tread 10B with 1 ops (10B / op)\n\
+             \tlargest op: read 10B, write 0B\n\
+             \tsyscalls: first op #2, last op #2\n\
+             FILE:/srv/data\n\
+             \tread 100B with 1 ops (100B / op)\n\
+             \tlargest op: read 100B, write 0B\n\
+             \tsyscalls: first op #6, last op #6\n\
+             FILE:/srv/data\n\
+             \tread 1000B with 1 ops (1000B / op)\n\
+             \tlargest op: read 1000B, write 0B\n\
+             \tsyscalls: first op #7, last op #7\n\
+             FILE:/srv/data\n"
+        ),
+        "{}",
+        output
+    );
+
+    // fds that aren't open keep the name of the link
+    assert!(output.contains("\nFILE:/dev/fd/9\n"), "{}", output);
+}
+
+#[test]
+fn lenient_skips_and_counts_interleaved_program_output() {
+    let output = output(
+        &["--lenient"],
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        hello from the program
+        read(3, "..."..., 10) = 10
+        Progress: 50%
+        read(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/data\n\
+         \tread 20B with 2 ops (10B / op)\n\
+         peak open descriptors: 4\n\
+         skipped 2 lines not matching the strace grammar\n",
+    );
+}
+
+#[test]
+fn openat_empty_path_reopens_the_referenced_fd() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_PATH) = 3
+        openat(3, "", O_RDONLY|AT_EMPTY_PATH) = 4
+        read(4, "..."..., 10) = 10
+        close(4) = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "FILE:/srv/data\n\
+             \tread 10B with 1 ops (10B / op)\n\
+             \tlargest op: read 10B, write 0B\n\
+             \tsyscalls: first op #3, last op #3\n\
+             FILE:/srv/data\n"
+        ),
+        "{}",
+        output
+    );
+    assert!(!output.contains("FILE:\n"), "{}", output);
+}
+
+#[test]
+fn op_sizes_are_min_avg_and_max_per_direction() {
+    let output = output(
+        &["--op-sizes", "-v"],
+        r#"
+        open("/srv/data", O_RDWR) = 3
+        read(3, "..."..., 100) = 100
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 0
+        write(3, "..."..., 10) = 10
+        close(3) = 0
+        open("/srv/idle", O_RDONLY) = 3
+        close(3) = 0
+        "#,
+    );
+
+    // the EOF isn't an op size
+    assert!(
+        output.contains(
+            "\tread op sizes: min 100B / avg 2.7K / max 4.0K\n\
+             \twrite op sizes: min 10B / avg 10B / max 10B\n"
+        ),
+        "{}",
+        output
+    );
+    assert_eq!(output.matches(" op sizes: ").count(), 2, "{}", output);
+}
+
+#[test]
+fn closes_of_fds_never_opened_are_counted_with_verbose() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        close(3) = 0
+        close(7) = 0
+        close(8) = 0
+        close(9) = -1 EBADF (Bad file descriptor)
+        "#;
+
+    assert!(output(&["-v"], trace).ends_with(
+        "\nclosed 2 fds that were never opened, the trace may have started mid-stream\n"
+    ));
+    assert!(!output(&[], trace).contains("never opened"));
+}
+
+#[test]
+fn positioned_vectored_io_is_classified_by_its_offset() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_RDWR) = 3
+        pwritev(3, [{iov_base="...", iov_len=512}, {iov_base="...", iov_len=512}], 2, 0) = 1024
+        pwritev(3, [{iov_base="...", iov_len=1024}], 1, 1024) = 1024
+        pwritev(3, [{iov_base="...", iov_len=1024}], 1, 8192) = 1024
+        write(3, "..."..., 100) = 100
+        write(3, "..."..., 100) = 100
+        preadv(3, [{iov_base="...", iov_len=200}], 1, 200) = 200
+        close(3) = 0
+        "#,
+    );
+
+    // the file pointer is still at 0 for the first write, it's only the
+    // pwritev at 8192 and that write that jump
+    assert!(
+        output.contains("\taccess: 3 sequential / 2 random ops\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn trace_span_ends_the_output_of_timed_traces() {
+    let trace = r#"
+        10:00:00.000000 open("/srv/data", O_RDONLY) = 3
+        10:00:00.500000 open("/srv/other", O_RDONLY) = 4
+        10:00:01.000000 read(3, "..."..., 100) = 100
+        10:00:02.500000 close(4) = 0
+        10:00:02.500000 close(3) = 0
+        10:00:02.600000 +++ exited with 2 +++
+        "#;
+
+    assert!(output(&[], trace).contains("\ntrace span: 2.6s\n"));
+    assert!(!output(&[], "open(\"/srv/data\", O_RDONLY) = 3\n").contains("trace span"));
+}
+
+#[test]
+fn vmsplice_writes_user_memory_to_the_pipe() {
+    let output = output(
+        &["-v"],
+        r#"
+        pipe([3, 4]) = 0
+        vmsplice(4, [{iov_base="...", iov_len=4096}, {iov_base="...", iov_len=4096}], 2, SPLICE_F_GIFT) = 6000
+        read(3, "..."..., 8192) = 6000
+        close(4) = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "PIPE\n\
+             \twrite 5.9K with 1 ops (8.0K / op)\n\
+             \tlargest op: read 0B, write 5.9K\n\
+             \tsyscalls: first op #2, last op #2\n\
+             PIPE\n\
+             \tread 5.9K with 1 ops (8.0K / op)\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn saved_summaries_render_like_the_trace_they_came_from() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        lseek(3, 0, SEEK_SET) = 0
+        read(3, "..."..., 4096) = 100
+        read(3, "", 4096) = 0
+        close(3) = 0
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3
+        connect(3, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+        write(3, "..."..., 64) = 64
+        close(3) = 0
+        "#,
+    );
+
+    let analyzed = traces.run(&["-v", "--save", "summaries.jsonl", "trace.strace"]);
+    let loaded = traces.run(&["-v", "--load", "summaries.jsonl"]);
+
+    // only the summaries are saved, the peak belongs to the run
+    assert_eq!(analyzed, format!("{}peak open descriptors: 4\n", loaded));
+    assert!(
+        loaded.contains("\taccess: 1 sequential / 1 random ops\n\tlargest op: read 4.0K, write 0B\n\treached EOF 1 times\n"),
+        "{}",
+        loaded
+    );
+}
+
+#[test]
+fn no_default_filter_shows_system_paths_without_verbose() {
+    let trace = r#"
+        openat(AT_FDCWD, "/usr/lib/libz.so.1", O_RDONLY|O_CLOEXEC) = 3
+        read(3, "..."..., 832) = 832
+        close(3) = 0
+        "#;
+
+    assert_eq!(output(&[], trace), "peak open descriptors: 4\n");
+
+    // only the path prefixes are let through, the verbose details stay off
+    assert_eq!(
+        output(&["--no-default-filter"], trace),
+        "FILE:/usr/lib/libz.so.1\n\
+         \tread 832B with 1 ops (832B / op)\n\
+         peak open descriptors: 4\n",
+    );
+}
+
+#[test]
+fn largest_op_counts_the_bytes_transferred_not_requested() {
+    let output = output(
+        &["-v"],
+        r#"
+        open("/srv/data", O_RDWR) = 3
+        read(3, "..."..., 65536) = 4000
+        read(3, "..."..., 1024) = 1024
+        write(3, "..."..., 8192) = 3000
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("\tread 4.9K with 2 ops (64.0K / op)\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("\tlargest op: read 3.9K, write 2.9K\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn readahead_and_fadvise_are_verbose_output() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        readahead(3, 0, 65536) = 0
+        readahead(3, 65536, 65536) = 0
+        fadvise64(3, 0, 0, POSIX_FADV_SEQUENTIAL) = 0
+        fadvise64(3, 0, 0, POSIX_FADV_RANDOM) = 0
+        fadvise64(3, 0, 4096, POSIX_FADV_WILLNEED) = 0
+        fadvise64(3, 0, 0, POSIX_FADV_DONTNEED) = 0
+        fadvise64(3, 0, 0, POSIX_FADV_SEQUENTIAL) = 0
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#;
+
+    assert!(!output(&[], trace).contains("prefetched"));
+    assert!(!output(&[], trace).contains("advice"));
+
+    let output = output(&["-v"], trace);
+    assert!(
+        output.contains("\tprefetched: 128.0K via readahead\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("\tadvice: SEQUENTIAL, RANDOM, WILLNEED, DONTNEED\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn anonymize_hides_every_name_in_every_format() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        open("/home/alice/secret.txt", O_RDONLY) = 3
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(3, "..."..., 4096) = 100
+        close(3) = 0
+        socket(AF_UNIX, SOCK_STREAM, 0) = 3
+        connect(3, {sa_family=AF_UNIX, sun_path="/home/alice/private.sock"}, 110) = 0
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        memfd_create("alice-scratch", MFD_CLOEXEC) = 3
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        mq_open("/alice-jobs", O_RDWR|O_CREAT, 0600, NULL) = 3
+        mq_timedsend(3, "hello", 5, 0, NULL) = 0
+        close(3) = 0
+        "#,
+    );
+
+    for format in &["text", "csv", "json", "table", "prometheus"] {
+        for relative in &["--relative-paths", "--relative-paths=/home/alice"] {
+            let (stdout, stderr) = traces.run_with_stderr(&[
+                "--anonymize",
+                "--format",
+                format,
+                relative,
+                "--retry-threshold",
+                "10",
+                "--top-errors",
+                "5",
+                "--hot-files",
+                "--no-default-filter",
+                "trace.strace",
+            ]);
+
+            assert!(!stdout.is_empty(), "{}", format);
+            assert!(!stdout.contains("alice"), "{}:\n{}", format, stdout);
+            assert!(!stdout.contains("secret"), "{}:\n{}", format, stdout);
+            assert!(!stderr.contains("alice"), "{}:\n{}", format, stderr);
+        }
+    }
+}
+
+#[test]
+fn include_zero_io_shows_files_that_were_only_opened() {
+    let trace = r#"
+        open("/srv/conf", O_RDONLY) = 3
+        close(3) = 0
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        close(3) = 0
+        "#;
+
+    assert!(!output(&[], trace).contains("/srv/conf"));
+    assert_eq!(
+        output(&["--include-zero-io"], trace),
+        "FILE:/srv/conf (no I/O)\n\
+         FILE:/srv/data\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         peak open descriptors: 4\n",
+    );
+}
+
+#[test]
+fn storms_flag_directories_opened_over_and_over_without_io() {
+    let mut trace = String::new();
+    for i in 0..100 {
+        trace.push_str(&format!(
+            "openat(AT_FDCWD, \"/srv/conf.d/{}.conf\", O_RDONLY) = 3\nclose(3) = 0\n",
+            i
+        ));
+    }
+    trace.push_str(
+        "open(\"/srv/data\", O_RDONLY) = 3\nread(3, \"...\"..., 10) = 10\nclose(3) = 0\n",
+    );
+
+    assert!(!output(&[], &trace).contains("OPEN STORMS"));
+    assert!(!output(&["--storms", "101"], &trace).contains("OPEN STORMS"));
+
+    let output = output(&["--storms", "100"], &trace);
+    assert!(
+        output.contains("\nOPEN STORMS:\n\t/srv/conf.d: 100 opens with at most 4.0K of I/O each\n"),
+        "{}",
+        output
+    );
+    assert!(!output.contains("\t/srv: "), "{}", output);
+}
+
+#[test]
+fn program_exit_ends_the_output() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        close(3) = 0
+        +++ exited with 2 +++
+        "#;
+
+    assert!(output(&[], trace).contains("\nprogram exited with 2\n"));
+
+    let clean = output(&[], "+++ exited with 0 +++\n");
+    assert!(clean.contains("program exited with 0\n"), "{}", clean);
+
+    let killed = output(&[], "+++ killed by SIGSEGV (core dumped) +++\n");
+    assert!(
+        killed.contains("program killed by SIGSEGV (core dumped)\n"),
+        "{}",
+        killed
+    );
+}
+
+#[test]
+fn pid_overview_shows_the_exits_instead() {
+    let output = output(
+        &["--pid-overview"],
+        "open(\"/srv/data\", O_RDONLY) = 3\n+++ exited with 2 +++\n",
+    );
+    assert!(output.contains("\texited with 2\n"), "{}", output);
+    assert!(!output.contains("program exited"), "{}", output);
+}
+
+#[test]
+fn duplicates_of_unknown_fds_are_never_bare_dup_rows() {
+    let trace = r#"
+        dup2(7, 5) = 5
+        write(5, "..."..., 10) = 10
+        close(5) = 0
+        "#;
+
+    assert!(!output(&[], trace).contains("DUP"));
+
+    let output = output(&["-v"], trace);
+    assert!(
+        output.starts_with("DUP:unresolved duplicate of fd 7\n\twrite 10B"),
+        "{}",
+        output
+    );
+    assert!(!output.lines().any(|line| line == "DUP"), "{}", output);
+}
+
+#[test]
+fn percent_shares_add_up_across_the_shown_files() {
+    let output = output(
+        &["--percent"],
+        r#"
+        open("/srv/a", O_RDWR) = 3
+        read(3, "..."..., 300) = 300
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        open("/srv/b", O_RDWR) = 3
+        read(3, "..."..., 100) = 100
+        write(3, "..."..., 300) = 300
+        close(3) = 0
+        open("/srv/c", O_WRONLY) = 3
+        write(3, "..."..., 160) = 160
+        close(3) = 0
+        "#,
+    );
+
+    let share = |direction: &str| -> f64 {
+        output
+            .lines()
+            .filter(|line| line.starts_with(&format!("\t{} ", direction)))
+            .map(|line| {
+                let percent = line.rsplit('\t').next().unwrap();
+                percent
+                    .trim_end_matches("% of total")
+                    .parse::<f64>()
+                    .unwrap()
+            })
+            .sum()
+    };
+
+    assert!((share("read") - 100.0).abs() < 0.2, "{}", output);
+    assert!((share("write") - 100.0).abs() < 0.2, "{}", output);
+    assert!(
+        output.contains("\tread 300B with 1 ops (300B / op)\t75.0% of total\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn sendmmsg_and_recvmmsg_count_every_message() {
+    let output = output(
+        &["-v"],
+        r#"
+        socket(AF_INET, SOCK_DGRAM, IPPROTO_IP) = 3
+        sendmmsg(3, [{msg_hdr={msg_name=NULL, msg_namelen=0, msg_iov=[{iov_base="...", iov_len=100}], msg_iovlen=1, msg_controllen=0, msg_flags=0}, msg_len=100}, {msg_hdr={msg_name=NULL, msg_namelen=0, msg_iov=[{iov_base="...", iov_len=60}], msg_iovlen=1, msg_controllen=0, msg_flags=0}, msg_len=60}], 2, 0) = 2
+        recvmmsg(3, [{msg_hdr={msg_name=NULL, msg_namelen=0, msg_iov=[{iov_base="...", iov_len=1500}], msg_iovlen=1, msg_controllen=0, msg_flags=0}, msg_len=40}], 8, 0, NULL) = 1
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with(
+            "SOCKET:\n\
+             \tread 40B with 1 ops (1.5K / op)\n\
+             \twrite 160B with 2 ops (100B / op)\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn compact_prints_one_line_per_file() {
+    let output = output(
+        &["--compact"],
+        r#"
+        open("/srv/data", O_RDWR) = 3
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 4096
+        write(3, "..."..., 1024) = 1024
+        close(3) = 0
+        open("/srv/ro", O_RDONLY) = 3
+        read(3, "..."..., 300) = 300
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "FILE:/srv/data R:8.0K/2ops W:1.0K/1ops\n\
+         FILE:/srv/ro R:300B/1ops W:0B/0ops\n\
+         peak open descriptors: 4\n",
+    );
+}
+
+#[test]
+fn lifetime_pairs_the_open_with_the_close_of_the_fd() {
+    let trace = r#"
+        10:00:00.000000 open("/srv/data", O_RDONLY) = 3
+        10:00:00.300000 read(3, "..."..., 100) = 100
+        10:00:01.500000 close(3) = 0
+        10:00:02.000000 open("/srv/other", O_RDONLY) = 3
+        10:00:02.000000 read(3, "..."..., 100) = 100
+        10:00:02.250000 close(3) = 0
+        "#;
+
+    assert!(!output(&[], trace).contains("\topen "));
+
+    let output = output(&["-v"], trace);
+    assert!(output.contains("FILE:/srv/data\n"), "{}", output);
+    assert!(
+        output.contains("\topen 1.5s\n\tfirst I/O 0.300s after open\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("\topen 0.2s\n\tfirst I/O 0.000s after open\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn blank_paths_of_an_s0_trace_are_named_by_annotation_or_number() {
+    let output = output(
+        &[],
+        r#"
+        openat(AT_FDCWD, ""..., O_RDONLY) = 3</srv/data>
+        read(3, ""..., 100) = 100
+        close(3) = 0
+        open(""..., O_WRONLY|O_CREAT, 0644) = 3
+        write(3, ""..., 50) = 50
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 100B with 1 ops (100B / op)\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("FILE:3 (unnamed)\n\twrite 50B with 1 ops (50B / op)\n"),
+        "{}",
+        output
+    );
+    assert!(!output.contains("FILE:\n"), "{}", output);
+}
+
+#[test]
+fn retry_rate_warns_about_sockets_busy_looping_on_eagain() {
+    let traces = Traces::new();
+    traces.file(
+        "trace.strace",
+        r#"
+        socket(AF_INET, SOCK_STREAM|SOCK_NONBLOCK, IPPROTO_TCP) = 3
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(3, "..."..., 4096) = 100
+        close(3) = 0
+        open("/srv/data", O_RDONLY) = 3
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EINTR (Interrupted system call)
+        read(3, "..."..., 4096) = 100
+        read(3, "..."..., 4096) = 100
+        close(3) = 0
+        "#,
+    );
+
+    let (_, stderr) = traces.run_with_stderr(&["trace.strace"]);
+    assert_eq!(
+        stderr,
+        "[warning] SOCKET:: 75% of the I/O attempts failed with EINTR/EAGAIN, busy-looping?\n",
+    );
+
+    let (_, stderr) = traces.run_with_stderr(&["--retry-threshold", "30", "trace.strace"]);
+    assert!(
+        stderr.contains("[warning] FILE:/srv/data: 33% of the I/O attempts"),
+        "{}",
+        stderr
+    );
+
+    let (_, stderr) = traces.run_with_stderr(&["--retry-threshold", "80", "trace.strace"]);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn per_tid_shares_the_fds_of_a_thread_group_only() {
+    let output = output(
+        &["--per-tid"],
+        r#"
+        100 open("/srv/data", O_RDONLY) = 3
+        100 clone(child_stack=0x7f, flags=CLONE_VM|CLONE_FS|CLONE_FILES|CLONE_SIGHAND|CLONE_THREAD|CLONE_SYSVSEM) = 101
+        100 clone(child_stack=NULL, flags=CLONE_CHILD_CLEARTID|SIGCHLD, child_tidptr=0x7f) = 200
+        101 read(3, "..."..., 10) = 10
+        100 read(3, "..."..., 100) = 100
+        200 close(3) = 0
+        200 open("/srv/other", O_RDONLY) = 3
+        200 read(3, "..."..., 1) = 1
+        101 read(3, "..."..., 20) = 20
+        100 close(3) = 0
+        "#,
+    );
+
+    let lines: Vec<&str> = output.lines().take(3).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "TID:100\tread 100B with 1 ops\twrite 0B with 0 ops",
+            "TID:101\tread 30B with 2 ops\twrite 0B with 0 ops",
+            "TID:200\tread 1B with 1 ops\twrite 0B with 0 ops",
+        ],
+    );
+
+    // the thread reads the fd of its process, even after the other process
+    // reused the number for another file
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 130B with 3 ops"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("FILE:/srv/other\n\tread 1B with 1 ops"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn max_descriptors_evicts_without_losing_the_totals() {
+    let mut trace = String::new();
+    for fd in 3..9 {
+        trace.push_str(&format!(
+            "open(\"/srv/f{0}\", O_RDONLY) = {0}\nread({0}, \"...\"..., 100) = 100\n",
+            fd
+        ));
+    }
+
+    let traces = Traces::new();
+    traces.file("trace.strace", &trace);
+
+    let (stdout, stderr) = traces.run_with_stderr(&["--max-descriptors", "4", "trace.strace"]);
+    assert!(
+        stderr.contains("[warning] too many descriptors"),
+        "{}",
+        stderr
+    );
+
+    // the standard streams go first, then the files read the longest ago,
+    // and all of them are still shown
+    assert_eq!(
+        stdout.matches("\tread 100B with 1 ops").count(),
+        6,
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains(
+            "\nevicted 5 descriptors before their close, later I/O on them is not counted\n"
+        ),
+        "{}",
+        stdout
+    );
+
+    let (stdout, stderr) = traces.run_with_stderr(&["trace.strace"]);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.matches("\tread 100B with 1 ops").count(), 6);
+    assert!(!stdout.contains("evicted"), "{}", stdout);
+}
+
+#[test]
+fn max_descriptors_keeps_the_fd_just_opened() {
+    let output = output(
+        &["--max-descriptors", "1"],
+        r#"
+        open("/srv/f", O_RDONLY) = 3
+        read(3, "..."..., 100) = 100
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.starts_with("FILE:/srv/f\n\tread 100B with 1 ops (100B / op)\n"),
+        "{}",
+        output
+    );
+    assert!(output.contains("\nevicted 3 descriptors"), "{}", output);
+}
+
+#[test]
+fn max_descriptors_evicts_the_least_recently_active_fd() {
+    let output = output(
+        &["--max-descriptors", "2"],
+        r#"
+        open("/srv/a", O_RDONLY) = 3
+        read(3, "..."..., 10) = 10
+        open("/srv/b", O_RDONLY) = 4
+        read(4, "..."..., 20) = 20
+        read(3, "..."..., 10) = 10
+        open("/srv/c", O_RDONLY) = 5
+        read(5, "..."..., 30) = 30
+        "#,
+    );
+
+    // /srv/b saw I/O after /srv/a did, but /srv/a was read again since
+    assert!(
+        output.starts_with("FILE:/srv/b\n\tread 20B with 1 ops (20B / op)\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("FILE:/srv/a\n\tread 20B with 2 ops (10B / op)\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("FILE:/srv/c\n\tread 30B with 1 ops (30B / op)\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn process_vm_readv_and_writev_are_attributed_to_the_peer() {
+    let output = output(
+        &[],
+        r#"
+        process_vm_readv(1234, [{iov_base=0x7ffd2c3e1a00, iov_len=4096}], 1, [{iov_base=0x5600a000, iov_len=4096}], 1, 0) = 4000
+        process_vm_writev(1234, [{iov_base=0x7ffd2c3e1a00, iov_len=100}], 1, [{iov_base=0x5600a000, iov_len=100}], 1, 0) = 100
+        process_vm_readv(99, [{iov_base=0x7ffd2c3e1a00, iov_len=10}], 1, [{iov_base=0x5600a000, iov_len=10}], 1, 0) = 10
+        "#,
+    );
+
+    assert_eq!(
+        output,
+        "PROCESS_MEMORY:pid 99\n\
+         \tread 10B with 1 ops (10B / op)\n\
+         PROCESS_MEMORY:pid 1234\n\
+         \tread 3.9K with 1 ops (4.0K / op)\n\
+         \twrite 100B with 1 ops (100B / op)\n",
+    );
+}
+
+#[test]
+fn hot_files_name_the_most_read_and_written_paths_first() {
+    let output = output(
+        &["--hot-files"],
+        r#"
+        open("/srv/a", O_RDWR) = 3
+        read(3, "..."..., 300) = 300
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        open("/srv/b", O_RDWR) = 3
+        read(3, "..."..., 500) = 500
+        write(3, "..."..., 300) = 300
+        close(3) = 0
+        open("/srv/a", O_RDONLY) = 3
+        read(3, "..."..., 300) = 300
+        close(3) = 0
+        "#,
+    );
+
+    // /srv/a is read the most over its two opens, though /srv/b had the
+    // biggest single descriptor
+    assert!(
+        output.starts_with(
+            "HOT FILES:\n\
+             \tmost read: /srv/a (600B)\n\
+             \tmost written: /srv/b (300B)\n\
+             FILE:/srv/a\n"
+        ),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn fields_select_and_order_the_csv_and_json_columns() {
+    let trace = r#"
+        open("/srv/data", O_RDWR) = 3
+        read(3, "..."..., 300) = 300
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        "#;
+
+    assert_eq!(
+        output(
+            &["--format", "csv", "--fields", "path,read_bytes,write_ops"],
+            trace
+        ),
+        "path,read_bytes,write_ops\n\
+         /srv/data,300,1\n",
+    );
+    assert_eq!(
+        output(&["--format", "json", "--fields", "write_ops,path"], trace),
+        "{\"write_ops\":1,\"path\":\"/srv/data\"}\n",
+    );
+
+    let traces = Traces::new();
+    traces.file("trace.strace", trace);
+    let stderr = traces.fail(&[
+        "--format",
+        "csv",
+        "--fields",
+        "path,read_byts",
+        "trace.strace",
+    ]);
+    assert!(
+        stderr.contains("'read_byts' isn't a valid value"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn fstat_size_gives_the_read_coverage() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        fstat(3, {st_mode=S_IFREG|0644, st_size=1048576, ...}) = 0
+        read(3, "..."..., 4096) = 4096
+        pread64(3, "..."..., 4096, 524288) = 4096
+        close(3) = 0
+        "#;
+
+    assert!(!output(&[], trace).contains("coverage"));
+
+    let output = output(&["-v"], trace);
+    assert!(
+        output.contains("\tread coverage: 0.8% of 1.0M\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn clone3_threads_of_an_ff_trace_share_the_fd_table() {
+    let traces = Traces::new();
+    traces.file(
+        "trace",
+        r#"
+        clone3({flags=CLONE_VM|CLONE_FILES|CLONE_THREAD, exit_signal=0}, 88) = 42
+        read(3, "..."..., 100) = 100
+        "#,
+    );
+
+    // the thread opens a file the process reads from afterwards
+    traces.file(
+        "trace.42",
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        "#,
+    );
+
+    let output = traces.run(&["trace"]);
+
+    assert!(
+        output.contains("FILE:/srv/data\n\tread 4.1K with 2 ops"),
+        "{}",
+        output
+    );
+}
+
+const SIZES: &str = r#"
+    open("/srv/data", O_RDWR) = 3
+    read(3, "..."..., 4096) = 4096
+    read(3, "..."..., 4096) = 4096
+    read(3, "..."..., 4096) = 4096
+    read(3, "..."..., 4096) = 4096
+    read(3, "..."..., 512) = 512
+    read(3, "..."..., 512) = 512
+    write(3, "..."..., 100) = 100
+    close(3) = 0
+    "#;
+
+#[test]
+fn histograms_are_plain_numbers_off_terminals() {
+    let traces = Traces::new();
+    traces.file("trace.strace", SIZES);
+
+    let output = traces.run(&["--global-histogram", "--op-sizes", "trace.strace"]);
+    assert!(
+        output.contains(
+            "OP SIZES:\n\
+             \tread 512B: 2 ops\n\
+             \tread 4.0K: 4 ops\n\
+             \twrite 100B: 1 ops\n"
+        ),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains(
+            "\t\tread 512B: 2 ops\n\
+             \t\tread 4.0K: 4 ops\n\
+             \t\twrite 100B: 1 ops\n"
+        ),
+        "{}",
+        output
+    );
+    assert!(!output.contains('█'), "{}", output);
+}
+
+#[test]
+fn histogram_bars_scale_with_the_counts_within_the_columns() {
+    let traces = Traces::new();
+    traces.file("trace.strace", SIZES);
+
+    for &columns in &[40, 80] {
+        let output = traces.run_with_columns(
+            columns,
+            &[
+                "--color",
+                "always",
+                "--global-histogram",
+                "--op-sizes",
+                "trace.strace",
+            ],
+        );
+
+        let rows: Vec<&str> = output
+            .lines()
+            .filter(|line| line.contains(": ") && line.contains(" ops "))
+            .collect();
+        assert_eq!(rows.len(), 6, "{}", output);
+
+        for row in &rows {
+            // tabs are eight columns, the escape sequences none
+            let plain = row.replace("\x1b[36m", "").replace("\x1b[0m", "");
+            let width: usize = plain.chars().map(|c| if c == '\t' { 8 } else { 1 }).sum();
+            assert!(width <= columns, "{} > {}: {:?}", width, columns, row);
+            assert!(row.contains("\x1b[36m"), "{:?}", row);
+        }
+
+        // the largest count fills the bar, half of it half the bar
+        let bar = |row: &str| row.chars().filter(|c| *c == '█').count();
+        for rows in rows.chunks(3) {
+            assert_eq!(bar(rows[1]), 2 * bar(rows[0]), "{}", output);
+            assert!(bar(rows[2]) < bar(rows[0]), "{}", output);
+        }
+    }
+
+    // off terminals, only --color always draws the bars
+    let output = traces.run_with_columns(
+        80,
+        &["--color", "never", "--global-histogram", "trace.strace"],
+    );
+    assert!(!output.contains('█'), "{}", output);
+}
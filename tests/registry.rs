@@ -0,0 +1,46 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::Traces;
+use strace_analyzer::analysis::{analyze_with_registry, standard_fds};
+use strace_analyzer::config::Config;
+use strace_analyzer::registry::Registry;
+use strace_analyzer::report::Report;
+
+#[test]
+fn registered_handlers_analyze_syscalls_the_defaults_ignore() {
+    let traces = Traces::new();
+    let input = traces.file(
+        "trace.strace",
+        r#"
+        open("/srv/src", O_RDONLY) = 3
+        open("/srv/dst", O_WRONLY) = 4
+        copy_file_range(3, NULL, 4, NULL, 8192, 0) = 8192
+        read(3, "..."..., 100) = 100
+        "#,
+    );
+
+    let mut registry = Registry::default();
+    registry.register("copy_file_range", |line, fds, report, _config| {
+        let args: Vec<&str> = line["copy_file_range(".len()..].split(", ").collect();
+        let bytes: u64 = line.rsplit("= ").next().unwrap().parse().unwrap();
+
+        if let Some(summary) = fds.get_mut(&args[0].parse().unwrap()) {
+            summary.update_read(bytes, bytes, report.seq);
+        }
+
+        if let Some(summary) = fds.get_mut(&args[2].parse().unwrap()) {
+            summary.update_write(bytes, bytes, report.seq);
+        }
+    });
+
+    let mut report = Report::new();
+    let config = Config::default();
+    analyze_with_registry(&mut standard_fds(), &input, &registry, &mut report, &config).unwrap();
+
+    // the built-in handlers still see the rest of the trace
+    let merged = report.merged_by_path();
+    assert_eq!(merged["/srv/src"].read_bytes(), 8292);
+    assert_eq!(merged["/srv/dst"].write_bytes(), 8192);
+}
@@ -0,0 +1,238 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::{analyze_str, analyze_str_with, summaries};
+use strace_analyzer::config::{Config, DEFAULT_TIERS};
+use strace_analyzer::summary::AccessMode;
+
+#[test]
+fn analyze_with_passes_each_summary_on_as_it_is_closed() {
+    let summaries = summaries(
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    // the closed file first, then STDIN, STDOUT and STDERR at the end
+    assert_eq!(summaries.len(), 4);
+    assert_eq!(summaries[0].descriptor.to_string(), "FILE:/srv/data");
+    assert_eq!(summaries[0].read_bytes(), 4096);
+}
+
+#[test]
+fn top_errors_rank_the_paths_by_their_failures() {
+    let report = analyze_str(
+        r#"
+        open("/srv/a", O_RDONLY) = 3
+        open("/srv/b", O_RDONLY) = 4
+        open("/srv/c", O_RDONLY) = 5
+        read(3, 0x7ffd2c3e1a00, 4096) = -1 EIO (Input/output error)
+        read(4, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(4, 0x7ffd2c3e1a00, 4096) = -1 EAGAIN (Resource temporarily unavailable)
+        read(4, 0x7ffd2c3e1a00, 4096) = -1 EINTR (Interrupted system call)
+        close(4) = 0
+        open("/srv/c", O_RDONLY) = 4
+        read(5, 0x7ffd2c3e1a00, 4096) = -1 EIO (Input/output error)
+        read(4, 0x7ffd2c3e1a00, 4096) = -1 EIO (Input/output error)
+        "#,
+    );
+
+    // the failures of both descriptors of /srv/c are combined
+    let top: Vec<String> = report
+        .top_errors(2)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(top, ["/srv/b", "/srv/c"]);
+}
+
+#[test]
+fn renames_relabel_the_closed_descriptors_of_the_file() {
+    let report = analyze_str(
+        r#"
+        openat(AT_FDCWD, "/srv/data.tmp", O_WRONLY|O_CREAT, 0644) = 3
+        write(3, "..."..., 4096) = 4096
+        close(3) = 0
+        rename("/srv/data.tmp", "/srv/data") = 0
+        "#,
+    );
+
+    let merged = report.merged_by_path();
+    assert_eq!(merged["/srv/data"].write_bytes(), 4096);
+    assert!(!merged.contains_key("/srv/data.tmp"));
+}
+
+#[test]
+fn syscall_stats_count_the_handled_syscalls() {
+    let report = analyze_str(
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        read(3, "", 4096) = 0
+        write(1, "..."..., 12) = 12
+        close(3) = 0
+        getpid() = 42
+        "#,
+    );
+
+    let stats: Vec<(&str, u64)> = report
+        .syscall_stats()
+        .iter()
+        .map(|(syscall, count)| (syscall.as_str(), *count))
+        .collect();
+    assert_eq!(
+        stats,
+        [("close", 1), ("open", 1), ("read", 2), ("write", 1)]
+    );
+}
+
+#[test]
+fn limit_lines_stops_the_analysis_early() {
+    let trace = r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 4096
+        "#;
+
+    let report = analyze_str(trace);
+    assert_eq!(report.truncated(), None);
+    assert_eq!(report.merged_by_path()["/srv/data"].read_bytes(), 12288);
+
+    let config = Config {
+        limit_lines: Some(2),
+        ..Config::default()
+    };
+    let report = analyze_str_with(trace, &config);
+    assert_eq!(report.truncated(), Some(2));
+    assert_eq!(report.merged_by_path()["/srv/data"].read_bytes(), 4096);
+}
+
+#[test]
+fn by_access_mode_splits_the_bytes_by_open_flags() {
+    let report = analyze_str(
+        r#"
+        open("/srv/app.conf", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 1000
+        openat(AT_FDCWD, "/srv/app.log", O_WRONLY|O_CREAT|O_APPEND, 0644) = 4
+        write(4, "..."..., 100) = 100
+        creat("/srv/out", 0644) = 5
+        write(5, "..."..., 200) = 200
+        open("/srv/db", O_RDWR) = 6
+        read(6, "..."..., 4096) = 4096
+        write(6, "..."..., 4096) = 4096
+        write(1, "..."..., 10) = 10
+        "#,
+    );
+
+    let modes = report.by_access_mode();
+
+    assert_eq!(modes[&Some(AccessMode::ReadOnly)], (1000, 0));
+    assert_eq!(modes[&Some(AccessMode::Append)], (0, 100));
+    assert_eq!(modes[&Some(AccessMode::WriteOnly)], (0, 200));
+    assert_eq!(modes[&Some(AccessMode::ReadWrite)], (4096, 4096));
+    // the standard streams weren't opened in the trace
+    assert_eq!(modes[&None], (0, 10));
+}
+
+#[test]
+fn merged_by_endpoint_combines_the_connections_of_a_pool() {
+    let mut trace = String::new();
+    for fd in 3..6 {
+        trace.push_str(&format!(
+            "socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = {0}\n\
+             connect({0}, {{sa_family=AF_INET, sin_port=htons(5432), \
+             sin_addr=inet_addr(\"10.0.0.1\")}}, 16) = 0\n\
+             write({0}, \"...\"..., 100) = 100\n\
+             close({0}) = 0\n",
+            fd
+        ));
+    }
+
+    let merged = analyze_str(&trace).merged_by_endpoint();
+    assert_eq!(merged.len(), 1);
+
+    let (endpoint, pool) = merged.iter().next().unwrap();
+    assert!(endpoint.contains("htons(5432)"), "{}", endpoint);
+    assert_eq!(pool.write_bytes(), 300);
+    assert_eq!(pool.write_ops(), 3);
+}
+
+#[test]
+fn global_distribution_merges_the_op_sizes_of_all_descriptors() {
+    let report = analyze_str(
+        r#"
+        open("/srv/a", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        read(3, "..."..., 4096) = 10
+        open("/srv/b", O_RDWR) = 4
+        read(4, "..."..., 4096) = 4096
+        read(4, "..."..., 512) = 512
+        write(4, "..."..., 100) = 100
+        write(1, "..."..., 100) = 100
+        "#,
+    );
+
+    // keyed by the requested size, not the bytes transferred
+    let (read, write) = report.global_distribution();
+    assert_eq!(read.into_iter().collect::<Vec<_>>(), [(512, 1), (4096, 3)]);
+    assert_eq!(write.into_iter().collect::<Vec<_>>(), [(100, 2)]);
+}
+
+#[test]
+fn tiers_count_the_files_by_their_mean_op_size() {
+    let report = analyze_str(
+        r#"
+        open("/srv/tiny", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 100
+        open("/srv/small", O_RDONLY) = 4
+        read(4, "..."..., 4096) = 512
+        read(4, "..."..., 4096) = 1024
+        open("/srv/medium", O_WRONLY) = 5
+        write(5, "..."..., 16384) = 16384
+        open("/srv/large", O_RDWR) = 6
+        read(6, "..."..., 131072) = 131072
+        write(6, "..."..., 65536) = 65536
+        open("/srv/unused", O_RDONLY) = 7
+        "#,
+    );
+
+    // files without any I/O have no mean op size to count
+    assert_eq!(report.tiers(DEFAULT_TIERS), [1, 1, 1, 1]);
+    assert_eq!(report.tiers([1024, 16384, 131072]), [2, 0, 2, 0]);
+}
+
+#[test]
+fn bytes_by_syscall_total_each_syscall_over_all_descriptors() {
+    let report = analyze_str(
+        r#"
+        open("/srv/data", O_RDWR) = 3
+        read(3, "..."..., 4096) = 4096
+        readv(3, [{iov_base="...", iov_len=512}, {iov_base="...", iov_len=512}], 2) = 1024
+        pread64(3, "..."..., 100, 0) = 100
+        read(3, "..."..., 4096) = 4096
+        pwrite64(3, "..."..., 10, 0) = 10
+        write(1, "..."..., 12) = 12
+        "#,
+    );
+
+    // pread64 and pwrite64 count under their plain names
+    let bytes: Vec<(&str, u64)> = report
+        .bytes_by_syscall()
+        .iter()
+        .map(|(syscall, bytes)| (syscall.as_str(), *bytes))
+        .collect();
+    assert_eq!(
+        bytes,
+        [
+            ("pread", 100),
+            ("pwrite", 10),
+            ("read", 8192),
+            ("readv", 1024),
+            ("write", 12)
+        ]
+    );
+}
@@ -0,0 +1,48 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::output;
+
+#[test]
+fn sync_counts_global_flushes_and_syncfs_per_filesystem() {
+    let output = output(
+        &["--sync"],
+        r#"
+        open("/srv/nfs/data", O_WRONLY) = 3
+        fstatfs(3, {f_type=NFS_SUPER_MAGIC, f_bsize=1048576, f_blocks=100, f_bfree=50}) = 0
+        open("/srv/local/data", O_WRONLY) = 4
+        fstatfs(4, {f_type=EXT2_SUPER_MAGIC, f_bsize=4096, f_blocks=100, f_bfree=50}) = 0
+        open("/srv/nfs/other", O_WRONLY) = 5
+        fstatfs(5, {f_type=NFS_SUPER_MAGIC, f_bsize=1048576, f_blocks=100, f_bfree=50}) = 0
+        open("/srv/unknown", O_WRONLY) = 6
+        write(3, "..."..., 10) = 10
+        fsync(3) = 0
+        syncfs(3) = 0
+        syncfs(5) = 0
+        syncfs(4) = 0
+        syncfs(6) = 0
+        sync() = 0
+        sync() = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert!(
+        output.contains("\tsync: 1 fsync, 0 fdatasync\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("sync: 2 global sync() calls\n"),
+        "{}",
+        output
+    );
+    assert!(output.contains("syncfs: 2 calls on nfs\n"), "{}", output);
+    assert!(output.contains("syncfs: 1 calls on ext4\n"), "{}", output);
+    assert!(
+        output.contains("syncfs: 1 calls on filesystems never statfs'ed\n"),
+        "{}",
+        output,
+    );
+}
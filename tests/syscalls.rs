@@ -0,0 +1,417 @@
+extern crate strace_analyzer;
+
+mod common;
+
+use common::{file, summaries, summaries_with};
+use strace_analyzer::config::Config;
+use strace_analyzer::summary::GenericFileDescriptor;
+
+#[test]
+fn o_tmpfile_opens_are_labeled_by_their_directory_until_linked() {
+    let summaries = summaries(
+        r#"
+        openat(AT_FDCWD, "/srv/tmp", O_RDWR|O_TMPFILE, 0600) = 3
+        write(3, "..."..., 4096) = 4096
+        write(3, "..."..., 4096) = 4096
+        close(3) = 0
+        openat(AT_FDCWD, "/srv/tmp", O_WRONLY|O_TMPFILE, 0600) = 3
+        write(3, "..."..., 100) = 100
+        linkat(AT_FDCWD, "/proc/self/fd/3", AT_FDCWD, "/srv/data", AT_SYMLINK_FOLLOW) = 0
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(
+        file(&summaries, "(O_TMPFILE in /srv/tmp)").write_bytes(),
+        8192
+    );
+    assert_eq!(file(&summaries, "/srv/data").write_bytes(), 100);
+}
+
+#[test]
+fn o_tmpfile_is_a_flag_not_part_of_the_path() {
+    let summaries = summaries(
+        r#"
+        openat(AT_FDCWD, "/srv/O_TMPFILE.log", O_WRONLY|O_CREAT, 0600) = 3
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        open("/srv/tmp", O_TMPFILE|O_WRONLY, 0600) = 3
+        write(3, "..."..., 10) = 10
+        close(3) = 0
+        "#,
+    );
+
+    assert_eq!(file(&summaries, "/srv/O_TMPFILE.log").write_bytes(), 100);
+    assert_eq!(
+        file(&summaries, "(O_TMPFILE in /srv/tmp)").write_bytes(),
+        10
+    );
+}
+
+#[test]
+fn redirected_standard_streams_count_for_the_file() {
+    let summaries = summaries(
+        r#"
+        open("/srv/app.log", O_WRONLY|O_CREAT, 0644) = 3
+        dup2(3, 1) = 1
+        open("/srv/err.log", O_WRONLY|O_CREAT, 0644) = 4
+        dup3(4, 2, O_CLOEXEC) = 2
+        close(3) = 0
+        close(4) = 0
+        write(1, "..."..., 100) = 100
+        write(2, "..."..., 10) = 10
+        "#,
+    );
+
+    assert_eq!(file(&summaries, "/srv/app.log").write_bytes(), 100);
+    assert_eq!(file(&summaries, "/srv/err.log").write_bytes(), 10);
+    assert_eq!(file(&summaries, "STDOUT").write_bytes(), 0);
+}
+
+#[test]
+fn message_queues_count_sent_messages_as_writes() {
+    let summaries = summaries(
+        r#"
+        mq_open("/jobs", O_RDWR|O_CREAT, 0600, NULL) = 3
+        mq_timedsend(3, "hello", 5, 0, NULL) = 0
+        mq_timedreceive(3, "hello, world", 8192, [0], NULL) = 12
+        close(3) = 0
+        "#,
+    );
+
+    let queues: Vec<(u64, u64)> = summaries
+        .iter()
+        .filter(|summary| summary.descriptor.to_string() == "MQ:/jobs")
+        .map(|summary| (summary.read_bytes(), summary.write_bytes()))
+        .collect();
+
+    assert_eq!(queues, [(12, 5)]);
+}
+
+#[test]
+fn terminals_are_labeled_as_ttys() {
+    let summaries = summaries(
+        r#"
+        openat(AT_FDCWD, "/dev/pts/3", O_RDWR) = 3
+        write(3, "$ ", 2) = 2
+        close(3) = 0
+        openat(AT_FDCWD, "/dev/tty", O_RDWR) = 3
+        read(3, "y\n", 1024) = 2
+        close(3) = 0
+        "#,
+    );
+
+    let ttys: Vec<(String, u64, u64)> = summaries
+        .iter()
+        .filter(|summary| summary.descriptor.name() == "tty")
+        .map(|summary| {
+            (
+                summary.descriptor.to_string(),
+                summary.read_bytes(),
+                summary.write_bytes(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        ttys,
+        [
+            (String::from("TTY:/dev/pts/3"), 0, 2),
+            (String::from("TTY:/dev/tty"), 2, 0)
+        ]
+    );
+}
+
+#[test]
+fn llseek_of_32_bit_traces_moves_the_offset() {
+    let summaries = summaries(
+        r#"
+        open("/srv/data", O_RDONLY|O_LARGEFILE) = 3
+        read(3, "..."..., 4096) = 4096
+        _llseek(3, 0, [0], SEEK_SET) = 0
+        read(3, "..."..., 4096) = 4096
+        pread64(3, "..."..., 4096, 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    let config = Config::default();
+    let data = file(&summaries, "/srv/data");
+
+    // the rewind makes the second read random, the pread continues it
+    assert_eq!(data.read_bytes(), 12288);
+    assert_eq!(data.field("sequential_ops", &config).as_u64(), Some(1));
+    assert_eq!(data.field("random_ops", &config).as_u64(), Some(1));
+}
+
+#[test]
+fn pidfd_getfd_labels_the_imported_fd_by_its_process() {
+    let summaries = summaries(
+        r#"
+        pidfd_open(1234, 0) = 3
+        pidfd_getfd(3, 7, 0) = 4
+        close(3) = 0
+        write(4, "..."..., 100) = 100
+        close(4) = 0
+        "#,
+    );
+
+    let closed: Vec<(String, u64)> = summaries
+        .iter()
+        .take(2)
+        .map(|summary| (summary.descriptor.to_string(), summary.write_bytes()))
+        .collect();
+
+    assert_eq!(
+        closed,
+        [
+            (String::from("PIDFD:pid 1234"), 0),
+            (String::from("IMPORTED:fd 7 of pid 1234"), 100)
+        ]
+    );
+}
+
+#[test]
+fn restarted_syscalls_count_as_retries_not_failures() {
+    let summaries = summaries(
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, 0x7ffd4c3e1000, 4096) = ? ERESTARTSYS (To be restarted if SA_RESTART is set)
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        open("/srv/other", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    let data = file(&summaries, "/srv/data");
+    assert_eq!(data.read_bytes(), 4096);
+    assert_eq!(data.read_ops(), 1);
+    assert_eq!(data.failures(), 0);
+    assert_eq!(data.retry_rate(), Some(0.5));
+
+    assert_eq!(file(&summaries, "/srv/other").retry_rate(), None);
+}
+
+#[test]
+fn hex_and_octal_escaped_paths_are_decoded() {
+    let summaries = summaries(
+        r#"
+        open("\x2f\x73\x72\x76\x2f\x64\x61\x74\x61", O_RDONLY) = 3
+        read(3, "\x00\x01", 4096) = 4096
+        close(3) = 0
+        open("/srv/caf\303\251", O_RDONLY) = 3
+        read(3, "\0\1", 100) = 100
+        close(3) = 0
+        "#,
+    );
+
+    // strace -xx
+    assert_eq!(file(&summaries, "/srv/data").read_bytes(), 4096);
+    assert_eq!(file(&summaries, "/srv/café").read_bytes(), 100);
+}
+
+#[test]
+fn nonblocking_connects_in_progress_keep_their_endpoint() {
+    let summaries = summaries(
+        r#"
+        socket(AF_INET, SOCK_STREAM|SOCK_NONBLOCK, IPPROTO_TCP) = 3
+        connect(3, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("10.0.0.1")}, 16) = -1 EINPROGRESS (Operation now in progress)
+        socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 4
+        connect(4, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.2")}, 16) = -1 ECONNREFUSED (Connection refused)
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        close(4) = 0
+        "#,
+    );
+
+    let endpoints: Vec<&str> = summaries
+        .iter()
+        .filter_map(|summary| match &summary.descriptor {
+            GenericFileDescriptor::Socket(socket_description) => Some(socket_description.connect()),
+            _ => None,
+        })
+        .collect();
+
+    // a refused connect never got anywhere
+    assert_eq!(endpoints.len(), 2);
+    assert!(endpoints[0].contains(r#"sin_addr=inet_addr("10.0.0.1")"#));
+    assert_eq!(endpoints[1], "");
+}
+
+#[test]
+fn writes_past_the_end_of_a_truncated_file_make_it_sparse() {
+    let summaries = summaries(
+        r#"
+        open("/srv/disk.img", O_WRONLY|O_CREAT|O_TRUNC, 0644) = 3
+        lseek(3, 1073741824, SEEK_SET) = 1073741824
+        write(3, "..."..., 4096) = 4096
+        close(3) = 0
+        open("/srv/log", O_WRONLY|O_CREAT|O_TRUNC, 0644) = 3
+        write(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    let image = file(&summaries, "/srv/disk.img");
+    assert_eq!(image.write_bytes(), 4096);
+    assert_eq!(image.sparse(), Some((1073745920, 1073741824)));
+
+    assert_eq!(file(&summaries, "/srv/log").sparse(), None);
+}
+
+#[test]
+fn fstatfs_records_the_filesystem_of_the_file() {
+    let summaries = summaries(
+        r#"
+        open("/mnt/data", O_RDONLY) = 3
+        fstatfs(3, {f_type=NFS_SUPER_MAGIC, f_bsize=1048576, f_blocks=100, f_bfree=50, f_bavail=50, f_files=10, f_ffree=5, f_fsid={val=[0, 0]}, f_namelen=255, f_frsize=1048576, f_flags=ST_VALID|ST_RELATIME}) = 0
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+
+    match file(&summaries, "/mnt/data").descriptor {
+        GenericFileDescriptor::File(file_description) => {
+            assert_eq!(file_description.filesystem.as_deref(), Some("nfs"));
+            assert_eq!(file_description.block_size, Some(1048576));
+        }
+        descriptor => panic!("not a file: {}", descriptor),
+    }
+}
+
+#[test]
+fn exclude_zero_byte_ops_skips_empty_writes() {
+    let trace = r#"
+        open("/srv/log", O_WRONLY) = 3
+        write(3, "", 0) = 0
+        write(3, "..."..., 100) = 100
+        write(3, "", 0) = 0
+        write(3, "..."..., 100) = 100
+        close(3) = 0
+        "#;
+
+    let log = file(&summaries(trace), "/srv/log");
+    assert_eq!((log.write_ops(), log.write_bytes()), (4, 200));
+
+    let config = Config {
+        exclude_zero_byte_ops: true,
+        ..Config::default()
+    };
+    let log = file(&summaries_with(trace, &config), "/srv/log");
+    assert_eq!((log.write_ops(), log.write_bytes()), (2, 200));
+}
+
+#[test]
+fn first_io_latency_starts_at_the_open_or_the_connect() {
+    let timed = summaries(
+        r#"
+        1700000000.000000 open("/srv/data", O_RDONLY) = 3
+        1700000000.250000 read(3, "..."..., 4096) = 4096
+        1700000001.000000 read(3, "..."..., 4096) = 4096
+        1700000001.000000 socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 4
+        1700000002.000000 connect(4, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.1")}, 16) = 0
+        1700000002.500000 write(4, "..."..., 100) = 100
+        1700000003.000000 close(3) = 0
+        1700000003.000000 close(4) = 0
+        "#,
+    );
+
+    let latencies: Vec<Option<f64>> = timed
+        .iter()
+        .take(2)
+        .map(|summary| summary.first_io_latency())
+        .collect();
+    assert_eq!(latencies, [Some(0.25), Some(0.5)]);
+
+    let untimed = summaries(
+        r#"
+        open("/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 4096) = 4096
+        close(3) = 0
+        "#,
+    );
+    assert_eq!(file(&untimed, "/srv/data").first_io_latency(), None);
+}
+
+#[test]
+fn raw_socketcalls_end_up_with_their_sockets() {
+    let summaries = summaries(
+        r#"
+        socketcall(SYS_SOCKET, [AF_INET, SOCK_STREAM, IPPROTO_TCP]) = 3
+        socketcall(0x3, [3, {sa_family=AF_INET, sin_port=htons(80), sin_addr=inet_addr("10.0.0.1")}, 16]) = 0
+        socketcall(SYS_SEND, [3, "...", 100, 0]) = 100
+        socketcall(SYS_RECV, [3, "...", 4096, 0]) = 40
+        close(3) = 0
+        "#,
+    );
+
+    let sockets: Vec<(&str, u64, u64)> = summaries
+        .iter()
+        .filter_map(|summary| match &summary.descriptor {
+            GenericFileDescriptor::Socket(socket_description) => Some((
+                socket_description.connect(),
+                summary.read_bytes(),
+                summary.write_bytes(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(sockets.len(), 1);
+    assert!(sockets[0].0.contains(r#"sin_addr=inet_addr("10.0.0.1")"#));
+    assert_eq!((sockets[0].1, sockets[0].2), (40, 100));
+}
+
+#[test]
+fn memfd_create_labels_the_fd_with_its_name() {
+    let summaries = summaries(
+        r#"
+        memfd_create("scratch", MFD_CLOEXEC) = 3
+        write(3, "..."..., 4096) = 4096
+        write(3, "..."..., 4096) = 4096
+        pread64(3, "..."..., 8192, 0) = 8192
+        close(3) = 0
+        "#,
+    );
+
+    let memfds: Vec<(u64, u64)> = summaries
+        .iter()
+        .filter(|summary| summary.descriptor.to_string() == "MEMFD:scratch")
+        .map(|summary| (summary.read_bytes(), summary.write_bytes()))
+        .collect();
+
+    assert_eq!(memfds, [(8192, 8192)]);
+}
+
+#[test]
+fn getrandom_and_dev_urandom_count_as_entropy() {
+    let summaries = summaries(
+        r#"
+        getrandom("\x1f\x8b, \x00"..., 32, GRND_NONBLOCK) = 32
+        getrandom("\x01\x02", 16, 0) = 16
+        openat(AT_FDCWD, "/dev/urandom", O_RDONLY) = 3
+        read(3, "..."..., 64) = 64
+        close(3) = 0
+        openat(AT_FDCWD, "/srv/data", O_RDONLY) = 3
+        read(3, "..."..., 64) = 64
+        close(3) = 0
+        "#,
+    );
+
+    let mut entropy: Vec<(String, u64)> = summaries
+        .iter()
+        .filter(|summary| summary.is_entropy())
+        .map(|summary| (summary.descriptor.to_string(), summary.read_bytes()))
+        .collect();
+    entropy.sort();
+
+    assert_eq!(
+        entropy,
+        [
+            (String::from("ENTROPY"), 48),
+            (String::from("FILE:/dev/urandom"), 64)
+        ]
+    );
+}